@@ -0,0 +1,180 @@
+use std::io::Read;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::borrow::Cow;
+use std::error::Error;
+use std::time::{Duration, Instant, SystemTime};
+use std::collections::HashMap;
+use std::str;
+
+use console::Console;
+
+/// Name of the built-in texture atlas, which block textures that the
+/// resource directories do not have are taken from.
+pub const ATLAS_ASSET: &str = "textures/minecraft.png";
+
+/// Minimum time between two checks for changed assets.
+const CHANGE_CHECK_INTERVAL_MS: u64 = 500;
+
+/// Assets that are compiled into the executable, by name. Resource
+/// directories use the same names as paths relative to the directory.
+const EMBEDDED: &[(&str, &[u8])] = &[
+    ("cube.obj", include_bytes!("../cube.obj")),
+    ("textures/minecraft.png", include_bytes!("../textures/minecraft.png")),
+    ("textures/font.png", include_bytes!("../textures/font.png")),
+    ("shader/block.vertex", include_bytes!("shader/block.vertex")),
+    ("shader/block.fragment", include_bytes!("shader/block.fragment")),
+    ("shader/outline.vertex", include_bytes!("shader/outline.vertex")),
+    ("shader/outline.fragment", include_bytes!("shader/outline.fragment")),
+    ("shader/overlay.vertex", include_bytes!("shader/overlay.vertex")),
+    ("shader/overlay.fragment", include_bytes!("shader/overlay.fragment")),
+    ("shader/hotbar.vertex", include_bytes!("shader/hotbar.vertex")),
+    ("shader/hotbar.fragment", include_bytes!("shader/hotbar.fragment")),
+    ("shader/text.vertex", include_bytes!("shader/text.vertex")),
+    ("shader/text.fragment", include_bytes!("shader/text.fragment")),
+];
+
+/// The embedded version of the asset ``name``.
+pub fn embedded(name: &str) -> Option<&'static [u8]> {
+    EMBEDDED.iter().find(|&&(embedded_name, _)| embedded_name == name).map(|&(_, bytes)| bytes)
+}
+
+/// Resolves assets such as textures, shaders and models by name. Files in
+/// the resource directories override the assets that are compiled in, so
+/// they can be replaced without recompiling, or even while running: the
+/// manager can report which assets have changed since they were loaded.
+pub struct AssetManager {
+    resource_dirs: Vec<PathBuf>,
+    /// The file every asset resolved to and its modification time when it
+    /// was last checked, or ``None`` for embedded assets.
+    versions: HashMap<String, Option<(PathBuf, Option<SystemTime>)>>,
+    last_check: Instant,
+}
+
+impl AssetManager {
+    /// Creates an asset manager that searches ``resource_dirs`` in order.
+    /// Directories that do not exist are skipped.
+    pub fn new<P>(resource_dirs: &[P]) -> AssetManager
+        where P: AsRef<Path>
+    {
+        let mut assets = AssetManager {
+            resource_dirs: resource_dirs.iter().map(|dir| dir.as_ref().to_path_buf()).collect(),
+            versions: HashMap::new(),
+            last_check: Instant::now(),
+        };
+        for &(name, _) in EMBEDDED {
+            assets.watch(name);
+        }
+        assets
+    }
+
+    /// Includes the asset ``name`` in the changes reported by
+    /// ``changed_assets``. Embedded assets are always watched; others only
+    /// exist in the resource directories.
+    pub fn watch(&mut self, name: &str) {
+        if !self.versions.contains_key(name) {
+            let version = self.version(name);
+            self.versions.insert(name.to_string(), version);
+        }
+    }
+
+    pub fn set_resource_dirs<P>(&mut self, resource_dirs: &[P])
+        where P: AsRef<Path>
+    {
+        self.resource_dirs = resource_dirs.iter().map(|dir| dir.as_ref().to_path_buf()).collect();
+    }
+
+    /// The file that overrides the asset ``name``, from the first resource
+    /// directory that has it.
+    pub fn find(&self, name: &str) -> Option<PathBuf> {
+        self.resource_dirs.iter().map(|dir| dir.join(name)).find(|path| path.is_file())
+    }
+
+    fn version(&self, name: &str) -> Option<(PathBuf, Option<SystemTime>)> {
+        self.find(name).map(|path| {
+            let modified = fs::metadata(&path).and_then(|metadata| metadata.modified()).ok();
+            (path, modified)
+        })
+    }
+
+    /// The names of the watched assets whose file has been modified, added or
+    /// removed since the previous call. Changing the resource directories
+    /// also changes the assets that resolve to different files. Checks at
+    /// most once every ``CHANGE_CHECK_INTERVAL_MS`` and returns nothing in
+    /// between.
+    pub fn changed_assets(&mut self) -> Vec<String> {
+        if self.last_check.elapsed() < Duration::from_millis(CHANGE_CHECK_INTERVAL_MS) {
+            return Vec::new();
+        }
+        self.last_check = Instant::now();
+
+        let mut changed = Vec::new();
+        let names: Vec<String> = self.versions.keys().cloned().collect();
+        for name in names {
+            let version = self.version(&name);
+            if self.versions.get(&name) != Some(&version) {
+                self.versions.insert(name.clone(), version);
+                changed.push(name);
+            }
+        }
+        changed.sort();
+        changed
+    }
+
+    /// The content of the asset ``name``, from a resource directory or else
+    /// the embedded version.
+    pub fn load(&self, name: &str) -> Result<Cow<'static, [u8]>, Box<dyn Error>> {
+        match self.find(name) {
+            Some(path) => {
+                let mut bytes = Vec::new();
+                File::open(&path)?.read_to_end(&mut bytes)?;
+                Ok(Cow::Owned(bytes))
+            },
+            None => embedded(name).map(Cow::Borrowed)
+                .ok_or_else(|| Box::<dyn Error>::from(format!("Unknown asset: ``{}``", name))),
+        }
+    }
+
+    pub fn load_string(&self, name: &str) -> Result<Cow<'static, str>, Box<dyn Error>> {
+        match self.load(name)? {
+            Cow::Borrowed(bytes) => Ok(Cow::Borrowed(str::from_utf8(bytes)?)),
+            Cow::Owned(bytes) => Ok(Cow::Owned(String::from_utf8(bytes)?)),
+        }
+    }
+
+    /// Builds something from the asset ``name`` with ``build``, which gets
+    /// the content of the asset and the path of the file it came from, if it
+    /// is not embedded.
+    pub fn build<T, F>(&self, name: &str, mut build: F) -> Result<T, Box<dyn Error>>
+        where F: FnMut(&[u8], Option<&Path>) -> Result<T, Box<dyn Error>>
+    {
+        match self.find(name) {
+            Some(path) => {
+                let mut bytes = Vec::new();
+                File::open(&path)?.read_to_end(&mut bytes)?;
+                build(&bytes, Some(&path))
+            },
+            None => {
+                let bytes = embedded(name).ok_or_else(|| Box::<dyn Error>::from(format!("Unknown asset: ``{}``", name)))?;
+                build(bytes, None)
+            },
+        }
+    }
+
+    /// Like ``build``, but if an override fails to load or build, the problem
+    /// is reported on ``console`` and the embedded version is used instead.
+    pub fn load_with<T, F>(&self, name: &str, mut build: F, console: &mut Console) -> Result<T, Box<dyn Error>>
+        where F: FnMut(&[u8], Option<&Path>) -> Result<T, Box<dyn Error>>
+    {
+        match self.build(name, &mut build) {
+            Ok(value) => Ok(value),
+            Err(e) => match (self.find(name), embedded(name)) {
+                (Some(path), Some(bytes)) => {
+                    console.print(&format!("Failed to load {}, using the built-in version: {}", path.display(), e));
+                    build(bytes, None)
+                },
+                _ => Err(e),
+            },
+        }
+    }
+}
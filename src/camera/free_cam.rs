@@ -2,28 +2,41 @@ extern crate cgmath;
 
 use std::f64::consts::PI;
 
-use glium::glutin::{ElementState, VirtualKeyCode, WindowEvent};
-use cgmath::{Deg, Point3, Vector3, Matrix4};
+use glium::glutin::WindowEvent;
+use cgmath::{Deg, Point3, Vector3, Matrix4, InnerSpace};
 
 use util::types::Float;
 use util::math::clamp;
+use input::action::Action;
+use config::settings::Settings;
 
-const MOUSE_SENSITIVITY: Float = 4.0;
-const MOVEMENT_SPEED: Float = 0.10;
+const SPRINT_MULTIPLIER: Float = 2.5;
 
 const MIN_ANGLE: Float = -PI as Float / 2.0 + 0.01;
 const MAX_ANGLE: Float = PI as Float / 2.0 - 0.01;
 
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum CameraMode {
+    /// Move freely along the viewing direction.
+    Fly,
+    /// Move on the horizontal plane, regardless of the vertical viewing angle.
+    /// Ascending and descending are ignored.
+    Walk,
+}
+
 #[derive(Debug)]
 pub struct CameraState {
     pub fov: Float,
     pub aspect_ratio: Float,
     pub znear: Float,
     pub zfar: Float,
+    pub mouse_sensitivity: Float,
+    pub movement_speed: Float,
 
     pub position: Point3<Float>,
     pub direction: Vector3<Float>,
     pub up: Vector3<Float>,
+    pub mode: CameraMode,
 
     horizontal_angle: Float,
     vertical_angle: Float,
@@ -32,28 +45,37 @@ pub struct CameraState {
     backward_pressed: bool,
     left_pressed: bool,
     right_pressed: bool,
+    up_pressed: bool,
+    down_pressed: bool,
+    sprint_pressed: bool,
 
     res: (u32, u32),
 }
 
 impl CameraState {
 
-    pub fn new(res: (u32, u32)) -> CameraState {
+    pub fn new(res: (u32, u32), settings: &Settings) -> CameraState {
         CameraState {
-            fov: 75.0,
+            fov: settings.fov,
             aspect_ratio: 4.0 / 3.0,
             position: Point3::new(0.0, 0.0, 0.0),
             direction: Vector3::new(0.0, 0.0, -1.0),
             up: Vector3::new(0.0, 1.0, 0.0),
+            mode: CameraMode::Fly,
             znear: 0.01,
             zfar: 1000.0,
+            mouse_sensitivity: settings.mouse_sensitivity,
+            movement_speed: settings.movement_speed,
             horizontal_angle: 0.0,
             vertical_angle: 0.0,
             forward_pressed: false,
             backward_pressed: false,
             left_pressed: false,
             right_pressed: false,
-            res: res,
+            up_pressed: false,
+            down_pressed: false,
+            sprint_pressed: false,
+            res,
         }
     }
 
@@ -61,25 +83,62 @@ impl CameraState {
         cgmath::perspective(Deg(self.fov), self.aspect_ratio, self.znear, self.zfar)
     }
 
+    pub fn apply_settings(&mut self, settings: &Settings) {
+        self.fov = settings.fov;
+        self.mouse_sensitivity = settings.mouse_sensitivity;
+        self.movement_speed = settings.movement_speed;
+    }
+
+    /// Horizontal and vertical viewing angle in degrees, with a yaw of 0
+    /// looking along +z.
+    pub fn yaw_pitch(&self) -> (Float, Float) {
+        (self.direction.x.atan2(self.direction.z).to_degrees(), self.direction.y.asin().to_degrees())
+    }
+
+    pub fn toggle_mode(&mut self) {
+        self.mode = match self.mode {
+            CameraMode::Fly => CameraMode::Walk,
+            CameraMode::Walk => CameraMode::Fly,
+        };
+    }
+
     pub fn update(&mut self) {
+        let speed = if self.sprint_pressed {
+            self.movement_speed * SPRINT_MULTIPLIER
+        } else {
+            self.movement_speed
+        };
+
+        let forward_vector = match self.mode {
+            CameraMode::Fly => self.direction,
+            CameraMode::Walk => Vector3::new(self.direction.x, 0.0, self.direction.z).normalize(),
+        };
+        let right_vector = self.direction.cross(Vector3::unit_y()).normalize();
+
         if self.forward_pressed {
-            self.position += self.direction * MOVEMENT_SPEED;
+            self.position += forward_vector * speed;
         } 
 
         if self.backward_pressed {
-            self.position += self.direction * -MOVEMENT_SPEED;
+            self.position += forward_vector * -speed;
         } 
 
         if self.left_pressed {
-            let mut right_vector = self.direction.cross(Vector3::unit_y());
-            right_vector = right_vector * MOVEMENT_SPEED;
-            self.position += -right_vector;
+            self.position += right_vector * -speed;
         }
         
         if self.right_pressed {
-            let mut right_vector = self.direction.cross(Vector3::unit_y());
-            right_vector = right_vector * MOVEMENT_SPEED;
-            self.position += right_vector; 
+            self.position += right_vector * speed; 
+        }
+
+        if self.mode == CameraMode::Fly {
+            if self.up_pressed {
+                self.position += Vector3::unit_y() * speed;
+            }
+
+            if self.down_pressed {
+                self.position += Vector3::unit_y() * -speed;
+            }
         }
     }
 
@@ -113,8 +172,7 @@ impl CameraState {
         if x < 0 {
             (0, 0)
         } else {
-            let delta = (midpoint.0 - x, midpoint.1 - y);
-            delta
+            (midpoint.0 - x, midpoint.1 - y)
         }
     }
 
@@ -127,28 +185,25 @@ impl CameraState {
             WindowEvent::MouseMoved { position, .. } => {
                 let (dx, dy) = self.calculate_mouse_delta(position.0 as i32, position.1 as i32);
                 let rotate_by = (
-                        (dx as Float / self.res.0 as Float) * MOUSE_SENSITIVITY, 
-                        (dy as Float / self.res.1 as Float) * MOUSE_SENSITIVITY / 2.0);
+                        (dx as Float / self.res.0 as Float) * self.mouse_sensitivity, 
+                        (dy as Float / self.res.1 as Float) * self.mouse_sensitivity / 2.0);
                 self.rotate(rotate_by);
             },
-            WindowEvent::KeyboardInput { input, .. } => {
-                match input.virtual_keycode {
-                    Some(VirtualKeyCode::W) => {
-                        self.forward_pressed = input.state == ElementState::Pressed;
-                    },
-                    Some(VirtualKeyCode::A) => {
-                        self.left_pressed = input.state == ElementState::Pressed;
-                    },
-                    Some(VirtualKeyCode::S) => {
-                        self.backward_pressed = input.state == ElementState::Pressed;
-                    },
-                    Some(VirtualKeyCode::D) => {
-                        self.right_pressed = input.state == ElementState::Pressed;
-                    },
-                    _ => (), 
-                };
-            },
             _ => (),
         };
     }
+
+    pub fn process_action(&mut self, action: Action, pressed: bool) {
+        match action {
+            Action::MoveForward => self.forward_pressed = pressed,
+            Action::MoveBackward => self.backward_pressed = pressed,
+            Action::MoveLeft => self.left_pressed = pressed,
+            Action::MoveRight => self.right_pressed = pressed,
+            Action::MoveUp => self.up_pressed = pressed,
+            Action::MoveDown => self.down_pressed = pressed,
+            Action::Sprint => self.sprint_pressed = pressed,
+            Action::ToggleFlight if pressed => self.toggle_mode(),
+            _ => (),
+        }
+    }
 }
@@ -0,0 +1,247 @@
+pub mod settings;
+
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::fs::File;
+use std::path::Path;
+use std::error::Error;
+use std::collections::BTreeMap;
+
+/// A value in a configuration file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+}
+
+impl Value {
+    fn parse(value_str: &str) -> Result<Value, Box<dyn Error>> {
+        if value_str.len() >= 2 && value_str.starts_with('"') && value_str.ends_with('"') {
+            Ok(Value::String(value_str[1..value_str.len() - 1].replace("\\\"", "\"").replace("\\\\", "\\")))
+        } else if value_str == "true" || value_str == "false" {
+            Ok(Value::Boolean(value_str == "true"))
+        } else if let Ok(i) = value_str.parse::<i64>() {
+            Ok(Value::Integer(i))
+        } else if let Ok(f) = value_str.parse::<f64>() {
+            Ok(Value::Float(f))
+        } else {
+            Err(Box::<dyn Error>::from(format!("Invalid value: ``{}``", value_str)))
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            Value::String(ref s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i64> {
+        match *self {
+            Value::Integer(i) => Some(i),
+            _ => None,
+        }
+    }
+
+    pub fn as_float(&self) -> Option<f64> {
+        match *self {
+            Value::Float(f) => Some(f),
+            Value::Integer(i) => Some(i as f64),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Boolean(b) => Some(b),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Value::String(ref s) => write!(f, "\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\"")),
+            Value::Integer(i) => write!(f, "{}", i),
+            Value::Float(x) => write!(f, "{:?}", x),
+            Value::Boolean(b) => write!(f, "{}", b),
+        }
+    }
+}
+
+pub type Section = BTreeMap<String, Value>;
+
+/// A configuration file in a small subset of TOML: ``[section]`` headers,
+/// ``key = value`` pairs with string, integer, float and boolean values, and
+/// ``#`` comments. Keys before the first header end up in the section ``""``.
+#[derive(Debug, Clone)]
+pub struct Config {
+    sections: BTreeMap<String, Section>,
+}
+
+impl Config {
+    pub fn new() -> Config {
+        Config { sections: BTreeMap::new() }
+    }
+
+    pub fn parse(config_str: &str) -> Result<Config, Box<dyn Error>> {
+        let mut config = Config::new();
+        let mut section = String::new();
+        for (line_nr, line) in config_str.lines().enumerate() {
+            let line = strip_comment(line).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if line.starts_with('[') && line.ends_with(']') {
+                section = line[1..line.len() - 1].trim().to_string();
+                config.sections.entry(section.clone()).or_default();
+            } else if let Some(i) = line.find('=') {
+                let key = line[..i].trim();
+                let value = Value::parse(line[i + 1..].trim()).map_err(|e| {
+                    Box::<dyn Error>::from(format!("Line {}: {}", line_nr + 1, e))
+                })?;
+                config.sections.entry(section.clone()).or_default().insert(key.to_string(), value);
+            } else {
+                return Err(Box::<dyn Error>::from(format!("Line {}: expected ``key = value``: ``{}``", line_nr + 1, line)));
+            }
+        }
+        Ok(config)
+    }
+
+    pub fn load<P>(path: P) -> Result<Config, Box<dyn Error>> 
+        where P: AsRef<Path>
+    {
+        let mut config_str = String::new();
+        File::open(path)?.read_to_string(&mut config_str)?;
+        Config::parse(&config_str)
+    }
+
+    /// Loads the configuration at ``path``, falling back to an empty
+    /// configuration when the file does not exist or cannot be parsed.
+    pub fn load_or_default<P>(path: P) -> Config 
+        where P: AsRef<Path>
+    {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Config::new();
+        }
+
+        Config::load(path).unwrap_or_else(|e| {
+            println!("Failed to load {}, using defaults: {}", path.display(), e);
+            Config::new()
+        })
+    }
+
+    pub fn section(&self, name: &str) -> Option<&Section> {
+        self.sections.get(name)
+    }
+
+    pub fn get(&self, section: &str, key: &str) -> Option<&Value> {
+        self.section(section).and_then(|s| s.get(key))
+    }
+
+    pub fn entries(&self) -> Vec<(&str, &str, &Value)> {
+        self.sections.iter()
+            .flat_map(|(name, section)| section.iter().map(move |(key, value)| (name.as_str(), key.as_str(), value)))
+            .collect()
+    }
+
+    pub fn set(&mut self, section: &str, key: &str, value: Value) {
+        self.sections.entry(section.to_string()).or_default().insert(key.to_string(), value);
+    }
+
+    pub fn save<P>(&self, path: P) -> io::Result<()> 
+        where P: AsRef<Path>
+    {
+        let mut f = File::create(path)?;
+        write!(f, "{}", self)
+    }
+}
+
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(section) = self.sections.get("") {
+            for (key, value) in section {
+                writeln!(f, "{} = {}", key, value)?;
+            }
+            writeln!(f)?;
+        }
+
+        for (name, section) in self.sections.iter().filter(|&(name, _)| !name.is_empty()) {
+            writeln!(f, "[{}]", name)?;
+            for (key, value) in section {
+                writeln!(f, "{} = {}", key, value)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' if !escaped => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => (),
+        }
+        escaped = c == '\\' && !escaped;
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, Value};
+
+    #[test]
+    fn parses_sections_and_values() {
+        let config = Config::parse("top = 1\n\
+                                    [window]\n\
+                                    title = \"a \\\"quoted\\\" name\"\n\
+                                    width = 800\n\
+                                    scale = 1.5\n\
+                                    vsync = true\n").unwrap();
+        assert_eq!(config.get("", "top"), Some(&Value::Integer(1)));
+        assert_eq!(config.get("window", "title"), Some(&Value::String("a \"quoted\" name".to_string())));
+        assert_eq!(config.get("window", "width"), Some(&Value::Integer(800)));
+        assert_eq!(config.get("window", "scale"), Some(&Value::Float(1.5)));
+        assert_eq!(config.get("window", "vsync"), Some(&Value::Boolean(true)));
+        assert_eq!(config.get("window", "missing"), None);
+    }
+
+    #[test]
+    fn integers_can_be_read_as_floats() {
+        assert_eq!(Value::Integer(3).as_float(), Some(3.0));
+        assert_eq!(Value::Float(3.0).as_integer(), None);
+    }
+
+    #[test]
+    fn ignores_comments_outside_strings() {
+        let config = Config::parse("# a comment\n[keys] # another\nname = \"#not a comment\" # but this is\n").unwrap();
+        assert_eq!(config.get("keys", "name"), Some(&Value::String("#not a comment".to_string())));
+    }
+
+    #[test]
+    fn reports_the_line_of_errors() {
+        let error = Config::parse("a = 1\n\nb = nonsense\n").unwrap_err();
+        assert!(error.to_string().starts_with("Line 3:"), "{}", error);
+        assert!(Config::parse("just some words\n").is_err());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let mut config = Config::new();
+        config.set("", "top", Value::Boolean(false));
+        config.set("camera", "fov", Value::Float(70.0));
+        config.set("camera", "name", Value::String("back\\slash \"quote\"".to_string()));
+        let parsed = Config::parse(&config.to_string()).unwrap();
+        assert_eq!(parsed.entries(), config.entries());
+    }
+}
@@ -0,0 +1,168 @@
+use std::error::Error;
+use std::path::Path;
+
+use config::{Config, Value};
+use input::action::Action;
+use input::bindings::Bindings;
+use util::types::Float;
+
+/// User settings, read from and saved to the settings file. Values that are
+/// missing or invalid fall back to their defaults.
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub window_width: u32,
+    pub window_height: u32,
+    pub vsync: bool,
+    pub fov: Float,
+    pub mouse_sensitivity: Float,
+    pub movement_speed: Float,
+    pub reach_distance: usize,
+    /// Number of edits that can be undone.
+    pub history_size: usize,
+    /// Directories with assets that override the built-in ones, searched in
+    /// order.
+    pub resource_dirs: Vec<String>,
+    pub bindings: Bindings,
+}
+
+pub const FOV_RANGE: (Float, Float) = (30.0, 120.0);
+const WINDOW_WIDTH_RANGE: (i64, i64) = (320, 7680);
+const WINDOW_HEIGHT_RANGE: (i64, i64) = (240, 4320);
+const MOUSE_SENSITIVITY_RANGE: (Float, Float) = (0.1, 20.0);
+const MOVEMENT_SPEED_RANGE: (Float, Float) = (0.01, 2.0);
+const REACH_DISTANCE_RANGE: (i64, i64) = (1, 16);
+const HISTORY_SIZE_RANGE: (i64, i64) = (0, 1000);
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            window_width: 640,
+            window_height: 480,
+            vsync: true,
+            fov: 75.0,
+            mouse_sensitivity: 4.0,
+            movement_speed: 0.10,
+            reach_distance: 5,
+            history_size: 100,
+            resource_dirs: vec!["resources".to_string()],
+            bindings: Bindings::new(),
+        }
+    }
+}
+
+fn read_integer(value: &Value, range: (i64, i64)) -> Result<i64, Box<dyn Error>> {
+    match value.as_integer() {
+        Some(i) if i >= range.0 && i <= range.1 => Ok(i),
+        Some(i) => Err(Box::<dyn Error>::from(format!("{} is out of range [{}, {}]", i, range.0, range.1))),
+        None => Err(Box::<dyn Error>::from(format!("expected an integer, got {}", value))),
+    }
+}
+
+fn read_float(value: &Value, range: (Float, Float)) -> Result<Float, Box<dyn Error>> {
+    match value.as_float() {
+        Some(f) if f as Float >= range.0 && f as Float <= range.1 => Ok(f as Float),
+        Some(f) => Err(Box::<dyn Error>::from(format!("{} is out of range [{}, {}]", f, range.0, range.1))),
+        None => Err(Box::<dyn Error>::from(format!("expected a number, got {}", value))),
+    }
+}
+
+fn read_bool(value: &Value) -> Result<bool, Box<dyn Error>> {
+    value.as_bool().ok_or_else(|| Box::<dyn Error>::from(format!("expected true or false, got {}", value)))
+}
+
+fn read_list(value: &Value) -> Result<Vec<String>, Box<dyn Error>> {
+    let list_str = value.as_str().ok_or_else(|| Box::<dyn Error>::from(format!("expected a comma separated list, got {}", value)))?;
+    Ok(list_str.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()).map(|s| s.to_string()).collect())
+}
+
+fn float_value(f: Float) -> Value {
+    // Round to get rid of the noise from widening the f32 setting to f64.
+    Value::Float((f as f64 * 1e6).round() / 1e6)
+}
+
+impl Settings {
+    /// Loads the settings from ``path``. Problems are reported on stdout and
+    /// the affected settings keep their default values.
+    pub fn load<P>(path: P) -> Settings 
+        where P: AsRef<Path>
+    {
+        let config = Config::load_or_default(path);
+        let mut settings = Settings::default();
+        for (section, key, value) in config.entries() {
+            if section == "bindings" {
+                continue;
+            }
+
+            if let Err(e) = settings.set_value(&format!("{}.{}", section, key), value) {
+                println!("Ignoring setting {}.{}: {}", section, key, e);
+            }
+        }
+
+        if let Err(e) = settings.bindings.load(&config) {
+            println!("Invalid key bindings: {}", e);
+        }
+        settings
+    }
+
+    pub fn save<P>(&self, path: P) -> Result<(), Box<dyn Error>> 
+        where P: AsRef<Path>
+    {
+        self.to_config().save(path)?;
+        Ok(())
+    }
+
+    /// Changes the setting with the name ``section.key`` from its textual
+    /// representation, validating it the same way as when loading.
+    pub fn set(&mut self, name: &str, value_str: &str) -> Result<(), Box<dyn Error>> {
+        let value = Value::parse(value_str)
+            .unwrap_or_else(|_| Value::String(value_str.to_string()));
+        self.set_value(name, &value)
+    }
+
+    /// The value of the setting with the name ``section.key``.
+    pub fn get(&self, name: &str) -> Option<Value> {
+        let mut parts = name.splitn(2, '.');
+        match (parts.next(), parts.next()) {
+            (Some(section), Some(key)) => self.to_config().get(section, key).cloned(),
+            _ => None,
+        }
+    }
+
+    fn set_value(&mut self, name: &str, value: &Value) -> Result<(), Box<dyn Error>> {
+        match name {
+            "window.width" => self.window_width = read_integer(value, WINDOW_WIDTH_RANGE)? as u32,
+            "window.height" => self.window_height = read_integer(value, WINDOW_HEIGHT_RANGE)? as u32,
+            "window.vsync" => self.vsync = read_bool(value)?,
+            "camera.fov" => self.fov = read_float(value, FOV_RANGE)?,
+            "camera.mouse_sensitivity" => self.mouse_sensitivity = read_float(value, MOUSE_SENSITIVITY_RANGE)?,
+            "camera.movement_speed" => self.movement_speed = read_float(value, MOVEMENT_SPEED_RANGE)?,
+            "game.reach_distance" => self.reach_distance = read_integer(value, REACH_DISTANCE_RANGE)? as usize,
+            "game.history_size" => self.history_size = read_integer(value, HISTORY_SIZE_RANGE)? as usize,
+            "assets.resource_dirs" => self.resource_dirs = read_list(value)?,
+            _ => return Err(Box::<dyn Error>::from(format!("Unknown setting: ``{}``", name))),
+        }
+        Ok(())
+    }
+
+    fn to_config(&self) -> Config {
+        let mut config = Config::new();
+        config.set("window", "width", Value::Integer(self.window_width as i64));
+        config.set("window", "height", Value::Integer(self.window_height as i64));
+        config.set("window", "vsync", Value::Boolean(self.vsync));
+        config.set("camera", "fov", float_value(self.fov));
+        config.set("camera", "mouse_sensitivity", float_value(self.mouse_sensitivity));
+        config.set("camera", "movement_speed", float_value(self.movement_speed));
+        config.set("game", "reach_distance", Value::Integer(self.reach_distance as i64));
+        config.set("game", "history_size", Value::Integer(self.history_size as i64));
+        config.set("assets", "resource_dirs", Value::String(self.resource_dirs.join(", ")));
+
+        for &action in Action::all() {
+            let mut inputs = self.bindings.inputs(action).iter()
+                .filter_map(|input| input.name())
+                .collect::<Vec<&str>>();
+            inputs.sort();
+            config.set("bindings", action.name(), Value::String(inputs.join(", ")));
+        }
+        config
+    }
+}
@@ -0,0 +1,198 @@
+use std::fmt;
+use std::error::Error;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandError {
+    /// The input does not contain a command.
+    Empty,
+    UnknownCommand(String),
+    /// The arguments do not match the usage of the command.
+    Usage(&'static str),
+    /// The command was understood, but could not be carried out.
+    Failed(String),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CommandError::Empty => write!(f, "No command given"),
+            CommandError::UnknownCommand(ref name) => write!(f, "Unknown command: {}", name),
+            CommandError::Usage(usage) => write!(f, "Usage: {}", usage),
+            CommandError::Failed(ref msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl Error for CommandError {
+    fn description(&self) -> &str {
+        "command failed"
+    }
+}
+
+/// A console command operating on a context of type ``C``.
+pub trait Command<C> {
+    fn name(&self) -> &'static str;
+
+    /// The command name followed by a description of its arguments.
+    fn usage(&self) -> &'static str;
+
+    /// Runs the command with the given arguments, returning a message for
+    /// the user on success.
+    fn execute(&self, ctx: &mut C, args: &[&str]) -> Result<String, CommandError>;
+}
+
+/// Parses command lines and runs them against the registered commands.
+pub struct Dispatcher<C> {
+    commands: BTreeMap<&'static str, Box<dyn Command<C>>>,
+}
+
+impl<C> Dispatcher<C> {
+    pub fn new() -> Dispatcher<C> {
+        Dispatcher { commands: BTreeMap::new() }
+    }
+
+    /// Registers ``command``, replacing any command with the same name.
+    pub fn register(&mut self, command: Box<dyn Command<C>>) {
+        self.commands.insert(command.name(), command);
+    }
+
+    /// The usage strings of all registered commands, sorted by name.
+    pub fn usages(&self) -> Vec<&'static str> {
+        self.commands.values().map(|c| c.usage()).collect()
+    }
+
+    pub fn execute(&self, ctx: &mut C, line: &str) -> Result<String, CommandError> {
+        let tokens = tokenize(line);
+        let args = tokens.iter().map(|s| s.as_str()).collect::<Vec<&str>>();
+        match args.split_first() {
+            Some((&"help", _)) => Ok(self.usages().join("\n")),
+            Some((name, args)) => match self.commands.get(name) {
+                Some(command) => command.execute(ctx, args),
+                None => Err(CommandError::UnknownCommand(name.to_string())),
+            },
+            None => Err(CommandError::Empty),
+        }
+    }
+}
+
+/// Splits ``line`` on whitespace, keeping text between double quotes together.
+fn tokenize(line: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut token = String::new();
+    let mut in_quotes = false;
+    let mut has_token = false;
+    for c in line.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                has_token = true;
+            },
+            c if c.is_whitespace() && !in_quotes => {
+                if has_token {
+                    tokens.push(token.clone());
+                    token.clear();
+                    has_token = false;
+                }
+            },
+            c => {
+                token.push(c);
+                has_token = true;
+            },
+        }
+    }
+
+    if has_token {
+        tokens.push(token);
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{tokenize, Command, CommandError, Dispatcher};
+
+    /// Adds its arguments to the context.
+    struct Add;
+
+    impl Command<i64> for Add {
+        fn name(&self) -> &'static str { "add" }
+
+        fn usage(&self) -> &'static str { "add <number>..." }
+
+        fn execute(&self, ctx: &mut i64, args: &[&str]) -> Result<String, CommandError> {
+            if args.is_empty() {
+                return Err(CommandError::Usage(self.usage()));
+            }
+            for arg in args {
+                *ctx += arg.parse::<i64>().map_err(|_| CommandError::Usage(self.usage()))?;
+            }
+            Ok(format!("Total: {}", ctx))
+        }
+    }
+
+    struct Echo;
+
+    impl Command<i64> for Echo {
+        fn name(&self) -> &'static str { "echo" }
+
+        fn usage(&self) -> &'static str { "echo <text>..." }
+
+        fn execute(&self, _: &mut i64, args: &[&str]) -> Result<String, CommandError> {
+            Ok(args.join("|"))
+        }
+    }
+
+    fn dispatcher() -> Dispatcher<i64> {
+        let mut dispatcher = Dispatcher::new();
+        dispatcher.register(Box::new(Echo));
+        dispatcher.register(Box::new(Add));
+        dispatcher
+    }
+
+    #[test]
+    fn tokenize_splits_on_whitespace() {
+        assert_eq!(tokenize("  fill 1\t2   3 "), vec!["fill", "1", "2", "3"]);
+        assert!(tokenize("   ").is_empty());
+    }
+
+    #[test]
+    fn tokenize_keeps_quoted_text_together() {
+        assert_eq!(tokenize("save \"my house\" now"), vec!["save", "my house", "now"]);
+        assert_eq!(tokenize("set name \"\""), vec!["set", "name", ""]);
+        assert_eq!(tokenize("a\"b c\"d"), vec!["ab cd"]);
+    }
+
+    #[test]
+    fn runs_commands_with_their_arguments() {
+        let dispatcher = dispatcher();
+        let mut total = 0;
+        assert_eq!(dispatcher.execute(&mut total, "add 1 2 3"), Ok("Total: 6".to_string()));
+        assert_eq!(total, 6);
+        assert_eq!(dispatcher.execute(&mut total, "echo \"a b\" c"), Ok("a b|c".to_string()));
+    }
+
+    #[test]
+    fn reports_unknown_and_missing_commands() {
+        let dispatcher = dispatcher();
+        let mut total = 0;
+        assert_eq!(dispatcher.execute(&mut total, "subtract 1"), Err(CommandError::UnknownCommand("subtract".to_string())));
+        assert_eq!(dispatcher.execute(&mut total, "   "), Err(CommandError::Empty));
+    }
+
+    #[test]
+    fn reports_usage_for_invalid_arguments() {
+        let dispatcher = dispatcher();
+        let mut total = 0;
+        assert_eq!(dispatcher.execute(&mut total, "add one"), Err(CommandError::Usage("add <number>...")));
+        assert_eq!(dispatcher.execute(&mut total, "add"), Err(CommandError::Usage("add <number>...")));
+        assert_eq!(total, 0);
+    }
+
+    #[test]
+    fn help_lists_usages_sorted_by_name() {
+        let dispatcher = dispatcher();
+        let mut total = 0;
+        assert_eq!(dispatcher.execute(&mut total, "help"), Ok("add <number>...\necho <text>...".to_string()));
+    }
+}
@@ -0,0 +1,654 @@
+use std::str::FromStr;
+use std::path::{Path, PathBuf};
+use std::fs::File;
+use std::error::Error;
+
+use cgmath::Point3;
+
+use console::command::{Command, CommandError, Dispatcher};
+use game::Game;
+use model::block::{self, BlockType};
+use loader::vox_loader::{self, VoxLoader};
+use exporter::MeshExporter;
+use exporter::obj_exporter::ObjExporter;
+use exporter::ply_exporter::PlyExporter;
+use util::constants::SETTINGS_FILE;
+use world::region::Region;
+use world::schematic::{self, Schematic};
+use world::mesher;
+use model::atlas::TextureAtlas;
+
+/// Registers the built-in commands that operate on the game.
+pub fn register_all<'a>(dispatcher: &mut Dispatcher<Game<'a>>) {
+    dispatcher.register(Box::new(Teleport));
+    dispatcher.register(Box::new(Time));
+    dispatcher.register(Box::new(Give));
+    dispatcher.register(Box::new(Slot));
+    dispatcher.register(Box::new(RegionEdit(RegionOp::Fill)));
+    dispatcher.register(Box::new(RegionEdit(RegionOp::Hollow)));
+    dispatcher.register(Box::new(RegionEdit(RegionOp::Walls)));
+    dispatcher.register(Box::new(Replace));
+    dispatcher.register(Box::new(Select));
+    dispatcher.register(Box::new(CopyBlocks));
+    dispatcher.register(Box::new(PasteBlocks));
+    dispatcher.register(Box::new(Rotate));
+    dispatcher.register(Box::new(Mirror));
+    dispatcher.register(Box::new(SchematicFile));
+    dispatcher.register(Box::new(Vox));
+    dispatcher.register(Box::new(Export));
+    dispatcher.register(Box::new(Undo));
+    dispatcher.register(Box::new(Redo));
+    dispatcher.register(Box::new(Save));
+    dispatcher.register(Box::new(Set));
+    dispatcher.register(Box::new(Get));
+    dispatcher.register(Box::new(Toggle));
+}
+
+fn parse_args<T>(args: &[&str], usage: &'static str) -> Result<Vec<T>, CommandError> 
+    where T: FromStr
+{
+    args.iter().map(|arg| arg.parse::<T>().map_err(|_| CommandError::Usage(usage))).collect()
+}
+
+fn parse_block_type(name: &str) -> Result<BlockType, CommandError> {
+    BlockType::from_name(name).ok_or_else(|| CommandError::Failed(format!("Unknown block type: {}", name)))
+}
+
+/// Parses the two corners of a region from six coordinates.
+fn parse_region(args: &[&str], usage: &'static str) -> Result<Region, CommandError> {
+    let coords = parse_args::<usize>(args, usage)?;
+    Ok(Region::new(&Point3::new(coords[0], coords[1], coords[2]), &Point3::new(coords[3], coords[4], coords[5])))
+}
+
+/// Splits off the region that the leading arguments describe. Commands that
+/// take a region accept either six coordinates or nothing, in which case the
+/// current selection is used. ``rest`` is the number of arguments that
+/// follow the region.
+fn region_args<'b, 'c>(game: &Game, args: &'b [&'c str], rest: usize, usage: &'static str)
+    -> Result<(Region, &'b [&'c str]), CommandError>
+{
+    if args.len() == rest + 6 {
+        Ok((parse_region(&args[..6], usage)?, &args[6..]))
+    } else if args.len() == rest {
+        match game.selection().region() {
+            Some(region) => Ok((region, args)),
+            None => Err(CommandError::Failed("Nothing is selected".to_string())),
+        }
+    } else {
+        Err(CommandError::Usage(usage))
+    }
+}
+
+/// The part of ``region`` that lies within the world.
+fn clip_to_world(game: &Game, region: &Region) -> Result<Region, CommandError> {
+    region.clip(&game.world().world().size())
+        .ok_or_else(|| CommandError::Failed("The region lies outside the world".to_string()))
+}
+
+struct Teleport;
+
+impl<'a> Command<Game<'a>> for Teleport {
+    fn name(&self) -> &'static str { "tp" }
+
+    fn usage(&self) -> &'static str { "tp <x> <y> <z>" }
+
+    fn execute(&self, game: &mut Game<'a>, args: &[&str]) -> Result<String, CommandError> {
+        if args.len() != 3 {
+            return Err(CommandError::Usage(self.usage()));
+        }
+
+        let coords = parse_args::<f32>(args, self.usage())?;
+        game.camera_mut().position = Point3::new(coords[0], coords[1], coords[2]) * block::DIM;
+        Ok(format!("Teleported to {} {} {}", coords[0], coords[1], coords[2]))
+    }
+}
+
+struct Time;
+
+impl<'a> Command<Game<'a>> for Time {
+    fn name(&self) -> &'static str { "time" }
+
+    fn usage(&self) -> &'static str { "time [hour 0-24]" }
+
+    fn execute(&self, game: &mut Game<'a>, args: &[&str]) -> Result<String, CommandError> {
+        match args.len() {
+            0 => Ok(format!("The time is {:.1}", game.time_of_day())),
+            1 => {
+                let hour = parse_args::<f32>(args, self.usage())?[0];
+                if !(0.0..=24.0).contains(&hour) {
+                    return Err(CommandError::Usage(self.usage()));
+                }
+                game.set_time_of_day(hour);
+                Ok(format!("Set the time to {:.1}", hour))
+            },
+            _ => Err(CommandError::Usage(self.usage())),
+        }
+    }
+}
+
+struct Give;
+
+impl<'a> Command<Game<'a>> for Give {
+    fn name(&self) -> &'static str { "give" }
+
+    fn usage(&self) -> &'static str { "give <block>" }
+
+    fn execute(&self, game: &mut Game<'a>, args: &[&str]) -> Result<String, CommandError> {
+        if args.len() != 1 {
+            return Err(CommandError::Usage(self.usage()));
+        }
+
+        let block_type = parse_block_type(args[0])?;
+        if let BlockType::Void(_) = block_type {
+            return Err(CommandError::Failed(format!("Cannot place {}", args[0])));
+        }
+        game.hotbar_mut().set_selected_type(block_type);
+        Ok(format!("Put {} in the selected slot", args[0]))
+    }
+}
+
+struct Slot;
+
+impl<'a> Command<Game<'a>> for Slot {
+    fn name(&self) -> &'static str { "slot" }
+
+    fn usage(&self) -> &'static str { "slot <1-9>" }
+
+    fn execute(&self, game: &mut Game<'a>, args: &[&str]) -> Result<String, CommandError> {
+        if args.len() != 1 {
+            return Err(CommandError::Usage(self.usage()));
+        }
+
+        match parse_args::<usize>(args, self.usage())?[0] {
+            slot @ 1..=9 => {
+                game.hotbar_mut().select(slot - 1);
+                Ok(format!("Selected slot {}", slot))
+            },
+            _ => Err(CommandError::Usage(self.usage())),
+        }
+    }
+}
+
+/// Bulk edits that set the blocks of (part of) a region to a single type.
+#[derive(Copy, Clone)]
+enum RegionOp {
+    Fill,
+    Hollow,
+    Walls,
+}
+
+struct RegionEdit(RegionOp);
+
+impl<'a> Command<Game<'a>> for RegionEdit {
+    fn name(&self) -> &'static str {
+        match self.0 {
+            RegionOp::Fill => "fill",
+            RegionOp::Hollow => "hollow",
+            RegionOp::Walls => "walls",
+        }
+    }
+
+    fn usage(&self) -> &'static str {
+        match self.0 {
+            RegionOp::Fill => "fill [<x1> <y1> <z1> <x2> <y2> <z2>] <block>",
+            RegionOp::Hollow => "hollow [<x1> <y1> <z1> <x2> <y2> <z2>] <block>",
+            RegionOp::Walls => "walls [<x1> <y1> <z1> <x2> <y2> <z2>] <block>",
+        }
+    }
+
+    fn execute(&self, game: &mut Game<'a>, args: &[&str]) -> Result<String, CommandError> {
+        let (region, args) = region_args(game, args, 1, self.usage())?;
+        let block_type = parse_block_type(args[0])?;
+        let world = game.world_mut();
+        let count = match self.0 {
+            RegionOp::Fill => world.fill(&region, block_type),
+            RegionOp::Hollow => world.hollow(&region, block_type),
+            RegionOp::Walls => world.walls(&region, block_type),
+        };
+        Ok(format!("Changed {} blocks", count))
+    }
+}
+
+struct Replace;
+
+impl<'a> Command<Game<'a>> for Replace {
+    fn name(&self) -> &'static str { "replace" }
+
+    fn usage(&self) -> &'static str { "replace [<x1> <y1> <z1> <x2> <y2> <z2>] <from block> <to block>" }
+
+    fn execute(&self, game: &mut Game<'a>, args: &[&str]) -> Result<String, CommandError> {
+        let (region, args) = region_args(game, args, 2, self.usage())?;
+        let from = parse_block_type(args[0])?;
+        let to = parse_block_type(args[1])?;
+        let count = game.world_mut().replace(&region, from, to);
+        Ok(format!("Replaced {} blocks", count))
+    }
+}
+
+struct Select;
+
+impl<'a> Command<Game<'a>> for Select {
+    fn name(&self) -> &'static str { "sel" }
+
+    fn usage(&self) -> &'static str { "sel [<x1> <y1> <z1> <x2> <y2> <z2> | clear]" }
+
+    fn execute(&self, game: &mut Game<'a>, args: &[&str]) -> Result<String, CommandError> {
+        match args.len() {
+            0 => (),
+            1 if args[0] == "clear" => {
+                game.selection_mut().clear();
+                return Ok("Cleared selection".to_string());
+            },
+            6 => {
+                let region = parse_region(args, self.usage())?;
+                game.selection_mut().set(&region);
+            },
+            _ => return Err(CommandError::Usage(self.usage())),
+        }
+
+        match game.selection().region() {
+            Some(region) => Ok(format!("Selected {} {} {} to {} {} {} ({} blocks)",
+                region.min.x, region.min.y, region.min.z,
+                region.max.x, region.max.y, region.max.z,
+                region.volume())),
+            None => Ok("Nothing is selected".to_string()),
+        }
+    }
+}
+
+struct CopyBlocks;
+
+impl<'a> Command<Game<'a>> for CopyBlocks {
+    fn name(&self) -> &'static str { "copy" }
+
+    fn usage(&self) -> &'static str { "copy [<x1> <y1> <z1> <x2> <y2> <z2>]" }
+
+    fn execute(&self, game: &mut Game<'a>, args: &[&str]) -> Result<String, CommandError> {
+        let (region, _) = region_args(game, args, 0, self.usage())?;
+        let structure = game.world().world().copy(&region)
+            .ok_or_else(|| CommandError::Failed("The region lies outside the world".to_string()))?;
+        let count = structure.blocks().len();
+        let size = structure.size();
+        game.set_clipboard(structure);
+        Ok(format!("Copied {} blocks ({}x{}x{})", count, size.x, size.y, size.z))
+    }
+}
+
+struct PasteBlocks;
+
+impl<'a> Command<Game<'a>> for PasteBlocks {
+    fn name(&self) -> &'static str { "paste" }
+
+    fn usage(&self) -> &'static str { "paste [<x> <y> <z>] [skip_air]" }
+
+    fn execute(&self, game: &mut Game<'a>, args: &[&str]) -> Result<String, CommandError> {
+        let skip_air = args.last() == Some(&"skip_air");
+        let args = if skip_air { &args[..args.len() - 1] } else { args };
+        let origin = match args.len() {
+            0 => {
+                // Paste on top of the face that is looked at, like placing a
+                // block.
+                match game.target().and_then(|target| target.place_pos()) {
+                    Some(pos) => pos,
+                    None => return Err(CommandError::Failed("No target to paste at".to_string())),
+                }
+            },
+            3 => {
+                let coords = parse_args::<usize>(args, self.usage())?;
+                Point3::new(coords[0], coords[1], coords[2])
+            },
+            _ => return Err(CommandError::Usage(self.usage())),
+        };
+
+        let structure = match game.clipboard() {
+            Some(structure) => structure.clone(),
+            None => return Err(CommandError::Failed("The clipboard is empty".to_string())),
+        };
+        let count = game.world_mut().paste(&structure, &origin, skip_air);
+        Ok(format!("Pasted {} blocks at {} {} {}", count, origin.x, origin.y, origin.z))
+    }
+}
+
+struct Rotate;
+
+impl<'a> Command<Game<'a>> for Rotate {
+    fn name(&self) -> &'static str { "rotate" }
+
+    fn usage(&self) -> &'static str { "rotate [90|180|270]" }
+
+    fn execute(&self, game: &mut Game<'a>, args: &[&str]) -> Result<String, CommandError> {
+        let degrees = match args.len() {
+            0 => 90,
+            1 => parse_args::<usize>(args, self.usage())?[0],
+            _ => return Err(CommandError::Usage(self.usage())),
+        };
+        if degrees % 90 != 0 {
+            return Err(CommandError::Usage(self.usage()));
+        }
+
+        let rotated = match game.clipboard() {
+            Some(structure) => structure.rotated_y(degrees / 90),
+            None => return Err(CommandError::Failed("The clipboard is empty".to_string())),
+        };
+        game.set_clipboard(rotated);
+        Ok(format!("Rotated the clipboard by {} degrees", degrees % 360))
+    }
+}
+
+struct Mirror;
+
+impl<'a> Command<Game<'a>> for Mirror {
+    fn name(&self) -> &'static str { "mirror" }
+
+    fn usage(&self) -> &'static str { "mirror <x|y|z>" }
+
+    fn execute(&self, game: &mut Game<'a>, args: &[&str]) -> Result<String, CommandError> {
+        if args.len() != 1 {
+            return Err(CommandError::Usage(self.usage()));
+        }
+
+        let axis = match args[0] {
+            "x" => 0,
+            "y" => 1,
+            "z" => 2,
+            _ => return Err(CommandError::Usage(self.usage())),
+        };
+        let mirrored = match game.clipboard() {
+            Some(structure) => structure.mirrored(axis),
+            None => return Err(CommandError::Failed("The clipboard is empty".to_string())),
+        };
+        game.set_clipboard(mirrored);
+        Ok(format!("Mirrored the clipboard along {}", args[0]))
+    }
+}
+
+/// Adds the schematic extension to ``name`` if it has none.
+fn schematic_path(name: &str) -> PathBuf {
+    let mut path = PathBuf::from(name);
+    if path.extension().is_none() {
+        path.set_extension(schematic::EXTENSION);
+    }
+    path
+}
+
+struct SchematicFile;
+
+impl<'a> Command<Game<'a>> for SchematicFile {
+    fn name(&self) -> &'static str { "schem" }
+
+    fn usage(&self) -> &'static str { "schem save <file> [<key>=<value> ...] | schem load <file>" }
+
+    fn execute(&self, game: &mut Game<'a>, args: &[&str]) -> Result<String, CommandError> {
+        if args.len() < 2 {
+            return Err(CommandError::Usage(self.usage()));
+        }
+
+        let path = schematic_path(args[1]);
+        match args[0] {
+            "save" => {
+                let mut schematic = match game.clipboard() {
+                    Some(structure) => Schematic::new(structure.clone()),
+                    None => return Err(CommandError::Failed("The clipboard is empty".to_string())),
+                };
+                for arg in &args[2..] {
+                    let mut parts = arg.splitn(2, '=');
+                    match (parts.next(), parts.next()) {
+                        (Some(key), Some(value)) => schematic.metadata.insert(key.to_string(), value.to_string()),
+                        _ => return Err(CommandError::Usage(self.usage())),
+                    };
+                }
+
+                schematic.save(&path)
+                    .map_err(|e| CommandError::Failed(format!("Failed to save {}: {}", path.display(), e)))?;
+                Ok(format!("Saved the clipboard to {}", path.display()))
+            },
+            "load" if args.len() == 2 => {
+                let schematic = Schematic::load(&path)
+                    .map_err(|e| CommandError::Failed(format!("Failed to load {}: {}", path.display(), e)))?;
+                let size = schematic.structure.size();
+                let mut message = format!("Loaded {} ({}x{}x{}) into the clipboard", path.display(), size.x, size.y, size.z);
+                for (key, value) in &schematic.metadata {
+                    message += &format!("\n{}: {}", key, value);
+                }
+                game.set_clipboard(schematic.structure);
+                Ok(message)
+            },
+            _ => Err(CommandError::Usage(self.usage())),
+        }
+    }
+}
+
+struct Vox;
+
+impl<'a> Command<Game<'a>> for Vox {
+    fn name(&self) -> &'static str { "vox" }
+
+    fn usage(&self) -> &'static str { "vox import <file> | vox export <file> [<x1> <y1> <z1> <x2> <y2> <z2>]" }
+
+    fn execute(&self, game: &mut Game<'a>, args: &[&str]) -> Result<String, CommandError> {
+        if args.len() < 2 {
+            return Err(CommandError::Usage(self.usage()));
+        }
+
+        let filename = args[1];
+        match args[0] {
+            "import" if args.len() == 2 => {
+                let structure = VoxLoader::new().load_from_file(filename)
+                    .map_err(|e| CommandError::Failed(format!("Failed to import {}: {}", filename, e)))?;
+                let size = structure.size();
+                game.set_clipboard(structure);
+                Ok(format!("Imported {} ({}x{}x{}) into the clipboard", filename, size.x, size.y, size.z))
+            },
+            "export" => {
+                let (region, _) = region_args(game, &args[2..], 0, self.usage())?;
+                let region = clip_to_world(game, &region)?;
+                vox_loader::check_size(&region.size())
+                    .map_err(|e| CommandError::Failed(format!("Failed to export {}: {}", filename, e)))?;
+                // The clipped region lies within the world, so copying it succeeds.
+                let structure = game.world().world().copy(&region).unwrap();
+                vox_loader::save_vox(&structure, filename)
+                    .map_err(|e| CommandError::Failed(format!("Failed to export {}: {}", filename, e)))?;
+                Ok(format!("Exported {} blocks to {}", region.volume(), filename))
+            },
+            _ => Err(CommandError::Usage(self.usage())),
+        }
+    }
+}
+
+/// Writes the texture atlas that is in use next to ``filename``, so exported
+/// meshes can refer to it. Returns the file name of the texture.
+fn export_atlas(atlas: &TextureAtlas, filename: &str) -> Result<String, Box<dyn Error>> {
+    let path = Path::new(filename).with_extension("png");
+    atlas.image.write_png(File::create(&path)?)?;
+    path.file_name().and_then(|name| name.to_str()).map(|name| name.to_string())
+        .ok_or_else(|| Box::<dyn Error>::from(format!("Invalid file name: ``{}``", filename)))
+}
+
+struct Export;
+
+impl<'a> Command<Game<'a>> for Export {
+    fn name(&self) -> &'static str { "export" }
+
+    fn usage(&self) -> &'static str { "export <file.obj|file.ply> [<x1> <y1> <z1> <x2> <y2> <z2>]" }
+
+    fn execute(&self, game: &mut Game<'a>, args: &[&str]) -> Result<String, CommandError> {
+        if args.is_empty() {
+            return Err(CommandError::Usage(self.usage()));
+        }
+
+        let filename = args[0];
+        let (region, _) = region_args(game, &args[1..], 0, self.usage())?;
+        let region = clip_to_world(game, &region)?;
+        // The clipped region lies within the world, so copying it succeeds.
+        let mesh = mesher::build_mesh(&game.world().world().copy(&region).unwrap(), &game.block_atlas().uvs);
+        let result = match Path::new(filename).extension().and_then(|ext| ext.to_str()) {
+            Some("obj") => {
+                export_atlas(game.block_atlas(), filename).and_then(|texture| {
+                    ObjExporter::new(&texture).export_to_file(&mesh, filename)
+                })
+            },
+            Some("ply") => PlyExporter::new().export_to_file(&mesh, filename),
+            _ => return Err(CommandError::Usage(self.usage())),
+        };
+        result.map_err(|e| CommandError::Failed(format!("Failed to export {}: {}", filename, e)))?;
+        Ok(format!("Exported {} triangles to {}", mesh.indices.len() / 3, filename))
+    }
+}
+
+struct Undo;
+
+impl<'a> Command<Game<'a>> for Undo {
+    fn name(&self) -> &'static str { "undo" }
+
+    fn usage(&self) -> &'static str { "undo" }
+
+    fn execute(&self, game: &mut Game<'a>, args: &[&str]) -> Result<String, CommandError> {
+        if !args.is_empty() {
+            return Err(CommandError::Usage(self.usage()));
+        }
+
+        match game.world_mut().undo() {
+            Some(count) => Ok(format!("Undid the change of {} blocks", count)),
+            None => Err(CommandError::Failed("Nothing to undo".to_string())),
+        }
+    }
+}
+
+struct Redo;
+
+impl<'a> Command<Game<'a>> for Redo {
+    fn name(&self) -> &'static str { "redo" }
+
+    fn usage(&self) -> &'static str { "redo" }
+
+    fn execute(&self, game: &mut Game<'a>, args: &[&str]) -> Result<String, CommandError> {
+        if !args.is_empty() {
+            return Err(CommandError::Usage(self.usage()));
+        }
+
+        match game.world_mut().redo() {
+            Some(count) => Ok(format!("Redid the change of {} blocks", count)),
+            None => Err(CommandError::Failed("Nothing to redo".to_string())),
+        }
+    }
+}
+
+struct Save;
+
+impl<'a> Command<Game<'a>> for Save {
+    fn name(&self) -> &'static str { "save" }
+
+    fn usage(&self) -> &'static str { "save" }
+
+    fn execute(&self, game: &mut Game<'a>, args: &[&str]) -> Result<String, CommandError> {
+        if !args.is_empty() {
+            return Err(CommandError::Usage(self.usage()));
+        }
+
+        game.settings().save(SETTINGS_FILE)
+            .map(|_| format!("Saved settings to {}", SETTINGS_FILE))
+            .map_err(|e| CommandError::Failed(format!("Failed to save settings to {}: {}", SETTINGS_FILE, e)))
+    }
+}
+
+struct Set;
+
+impl<'a> Command<Game<'a>> for Set {
+    fn name(&self) -> &'static str { "set" }
+
+    fn usage(&self) -> &'static str { "set <section.setting> <value>" }
+
+    fn execute(&self, game: &mut Game<'a>, args: &[&str]) -> Result<String, CommandError> {
+        if args.len() != 2 {
+            return Err(CommandError::Usage(self.usage()));
+        }
+
+        game.set_setting(args[0], args[1])
+            .map(|_| format!("Set {} to {}", args[0], args[1]))
+            .map_err(|e| CommandError::Failed(e.to_string()))
+    }
+}
+
+struct Get;
+
+impl<'a> Command<Game<'a>> for Get {
+    fn name(&self) -> &'static str { "get" }
+
+    fn usage(&self) -> &'static str { "get <section.setting>" }
+
+    fn execute(&self, game: &mut Game<'a>, args: &[&str]) -> Result<String, CommandError> {
+        if args.len() != 1 {
+            return Err(CommandError::Usage(self.usage()));
+        }
+
+        game.settings().get(args[0])
+            .map(|value| format!("{} = {}", args[0], value))
+            .ok_or_else(|| CommandError::Failed(format!("Unknown setting: {}", args[0])))
+    }
+}
+
+struct Toggle;
+
+impl<'a> Command<Game<'a>> for Toggle {
+    fn name(&self) -> &'static str { "toggle" }
+
+    fn usage(&self) -> &'static str { "toggle <debug|flight|section.setting>" }
+
+    fn execute(&self, game: &mut Game<'a>, args: &[&str]) -> Result<String, CommandError> {
+        if args.len() != 1 {
+            return Err(CommandError::Usage(self.usage()));
+        }
+
+        match args[0] {
+            "debug" => {
+                game.toggle_debug();
+                Ok("Toggled the debug overlay".to_string())
+            },
+            "flight" => {
+                game.camera_mut().toggle_mode();
+                Ok(format!("Camera mode is now {:?}", game.camera().mode))
+            },
+            name => {
+                let value = game.settings().get(name).and_then(|value| value.as_bool())
+                    .ok_or_else(|| CommandError::Failed(format!("Not a boolean setting: {}", name)))?;
+                let new_value = if value { "false" } else { "true" };
+                game.set_setting(name, new_value)
+                    .map(|_| format!("Set {} to {}", name, new_value))
+                    .map_err(|e| CommandError::Failed(e.to_string()))
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::Point3;
+
+    use console::command::CommandError;
+    use model::block::BlockType;
+    use super::{parse_args, parse_block_type, parse_region};
+
+    const USAGE: &str = "test <args>";
+
+    #[test]
+    fn parse_args_reports_usage() {
+        assert_eq!(parse_args::<usize>(&["1", "2"], USAGE), Ok(vec![1, 2]));
+        assert_eq!(parse_args::<usize>(&["1", "-2"], USAGE), Err(CommandError::Usage(USAGE)));
+        assert_eq!(parse_args::<f32>(&["1.5", "x"], USAGE), Err(CommandError::Usage(USAGE)));
+    }
+
+    #[test]
+    fn parse_region_orders_corners() {
+        let region = parse_region(&["4", "0", "2", "1", "3", "2"], USAGE).unwrap();
+        assert_eq!(region.min, Point3::new(1, 0, 2));
+        assert_eq!(region.max, Point3::new(4, 3, 2));
+    }
+
+    #[test]
+    fn parse_block_type_accepts_names_and_indices() {
+        assert_eq!(parse_block_type("stone"), Ok(BlockType::Solid(1)));
+        assert_eq!(parse_block_type("1"), Ok(BlockType::Solid(1)));
+        assert_eq!(parse_block_type("air"), Ok(BlockType::Void(0)));
+        assert!(parse_block_type("cheese").is_err());
+    }
+}
@@ -0,0 +1,108 @@
+pub mod command;
+pub mod commands;
+
+use std::collections::VecDeque;
+
+/// Number of output lines kept by the console.
+const MAX_OUTPUT_LINES: usize = 100;
+
+/// The state of the in-game console: the line being typed, previously
+/// entered lines and the output of commands.
+pub struct Console {
+    open: bool,
+    input: String,
+    history: Vec<String>,
+    history_pos: usize,
+    output: VecDeque<String>,
+    ignore_next_char: bool,
+}
+
+impl Console {
+    pub fn new() -> Console {
+        Console {
+            open: false,
+            input: String::new(),
+            history: Vec::new(),
+            history_pos: 0,
+            output: VecDeque::new(),
+            ignore_next_char: false,
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Opens the console. The character typed by the key that opened the
+    /// console is not added to the input.
+    pub fn open(&mut self) {
+        self.open = true;
+        self.ignore_next_char = true;
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+        self.input.clear();
+    }
+
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    pub fn output(&self) -> &VecDeque<String> {
+        &self.output
+    }
+
+    pub fn print(&mut self, text: &str) {
+        for line in text.lines() {
+            if self.output.len() == MAX_OUTPUT_LINES {
+                self.output.pop_front();
+            }
+            self.output.push_back(line.to_string());
+        }
+    }
+
+    /// Handles a typed character.
+    pub fn type_char(&mut self, c: char) {
+        if self.ignore_next_char {
+            self.ignore_next_char = false;
+        } else if !c.is_control() {
+            self.input.push(c);
+        }
+    }
+
+    /// Must be called for every key press while the console is open.
+    pub fn key_pressed(&mut self) {
+        self.ignore_next_char = false;
+    }
+
+    pub fn backspace(&mut self) {
+        self.input.pop();
+    }
+
+    /// Replaces the input with the previous (``older == true``) or next line
+    /// in the history.
+    pub fn browse_history(&mut self, older: bool) {
+        if older && self.history_pos > 0 {
+            self.history_pos -= 1;
+        } else if !older && self.history_pos < self.history.len() {
+            self.history_pos += 1;
+        }
+        self.input = self.history.get(self.history_pos).cloned().unwrap_or_default();
+    }
+
+    /// Takes the current input line and adds it to the history.
+    pub fn submit(&mut self) -> Option<String> {
+        if self.input.trim().is_empty() {
+            return None;
+        }
+
+        let line = self.input.split_off(0);
+        if self.history.last() != Some(&line) {
+            self.history.push(line.clone());
+        }
+        self.history_pos = self.history.len();
+        self.print(&format!("> {}", line));
+        Some(line)
+    }
+}
@@ -0,0 +1,10 @@
+pub mod obj_exporter;
+pub mod ply_exporter;
+
+use std::error::Error;
+
+use model::mesh::Mesh;
+
+pub trait MeshExporter {
+    fn export_to_file(&self, mesh: &Mesh, filename: &str) -> Result<(), Box<dyn Error>>;
+}
@@ -0,0 +1,79 @@
+use std::io::{BufWriter, Write};
+use std::fs::File;
+use std::path::Path;
+use std::error::Error;
+
+use model::mesh::Mesh;
+use exporter::MeshExporter;
+
+const MATERIAL_NAME: &str = "blocks";
+
+/// Writes meshes as Wavefront ``.obj`` files, together with a ``.mtl``
+/// material library next to them that maps the mesh to a texture.
+pub struct ObjExporter {
+    texture_path: String,
+}
+
+impl ObjExporter {
+    /// Creates an exporter whose material refers to the texture at
+    /// ``texture_path``, relative to the exported files.
+    pub fn new(texture_path: &str) -> ObjExporter {
+        ObjExporter { texture_path: texture_path.to_string() }
+    }
+
+    pub fn write_obj<W: Write>(&self, mesh: &Mesh, mtl_filename: &str, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        writeln!(writer, "mtllib {}", mtl_filename)?;
+        writeln!(writer, "o blocks")?;
+        for vertex in &mesh.vertex_info {
+            writeln!(writer, "v {} {} {}", vertex.pos[0], vertex.pos[1], vertex.pos[2])?;
+        }
+        for vertex in &mesh.vertex_info {
+            writeln!(writer, "vt {} {}", vertex.tex_coords[0], vertex.tex_coords[1])?;
+        }
+        for vertex in &mesh.vertex_info {
+            writeln!(writer, "vn {} {} {}", vertex.norm[0], vertex.norm[1], vertex.norm[2])?;
+        }
+
+        writeln!(writer, "usemtl {}", MATERIAL_NAME)?;
+        // Every vertex has its own position, texture coordinate and normal,
+        // so all three share an index. Indices in .obj files start at 1.
+        for face in mesh.indices.chunks(3) {
+            write!(writer, "f")?;
+            for &i in face {
+                write!(writer, " {0}/{0}/{0}", i + 1)?;
+            }
+            writeln!(writer)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_mtl<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        writeln!(writer, "newmtl {}", MATERIAL_NAME)?;
+        writeln!(writer, "Ka 1.0 1.0 1.0")?;
+        writeln!(writer, "Kd 1.0 1.0 1.0")?;
+        writeln!(writer, "Ks 0.0 0.0 0.0")?;
+        writeln!(writer, "d 1.0")?;
+        writeln!(writer, "illum 1")?;
+        writeln!(writer, "map_Kd {}", self.texture_path)?;
+        Ok(())
+    }
+}
+
+impl MeshExporter for ObjExporter {
+    /// Writes ``filename`` and a material library with the same name and the
+    /// ``.mtl`` extension.
+    fn export_to_file(&self, mesh: &Mesh, filename: &str) -> Result<(), Box<dyn Error>> {
+        let mtl_path = Path::new(filename).with_extension("mtl");
+        let mtl_filename = mtl_path.file_name().and_then(|name| name.to_str())
+            .ok_or_else(|| Box::<dyn Error>::from(format!("Invalid file name: ``{}``", filename)))?;
+
+        let mut obj_writer = BufWriter::new(File::create(filename)?);
+        self.write_obj(mesh, mtl_filename, &mut obj_writer)?;
+        obj_writer.flush()?;
+
+        let mut mtl_writer = BufWriter::new(File::create(&mtl_path)?);
+        self.write_mtl(&mut mtl_writer)?;
+        mtl_writer.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,68 @@
+use std::io::{BufWriter, Write};
+use std::fs::File;
+use std::error::Error;
+
+use util::types::Float;
+use model::mesh::Mesh;
+use exporter::MeshExporter;
+
+/// Writes meshes as binary little endian ``.ply`` files with positions,
+/// normals and texture coordinates per vertex.
+pub struct PlyExporter;
+
+fn write_float<W: Write>(writer: &mut W, value: Float) -> Result<(), Box<dyn Error>> {
+    let bits = value.to_bits();
+    writer.write_all(&[bits as u8, (bits >> 8) as u8, (bits >> 16) as u8, (bits >> 24) as u8])?;
+    Ok(())
+}
+
+fn write_u32<W: Write>(writer: &mut W, value: u32) -> Result<(), Box<dyn Error>> {
+    writer.write_all(&[value as u8, (value >> 8) as u8, (value >> 16) as u8, (value >> 24) as u8])?;
+    Ok(())
+}
+
+impl PlyExporter {
+    pub fn new() -> PlyExporter {
+        PlyExporter
+    }
+
+    pub fn write_ply<W: Write>(&self, mesh: &Mesh, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        write!(writer, "ply\n\
+                        format binary_little_endian 1.0\n\
+                        element vertex {}\n\
+                        property float x\n\
+                        property float y\n\
+                        property float z\n\
+                        property float nx\n\
+                        property float ny\n\
+                        property float nz\n\
+                        property float s\n\
+                        property float t\n\
+                        element face {}\n\
+                        property list uchar uint vertex_indices\n\
+                        end_header\n",
+            mesh.vertex_info.len(), mesh.indices.len() / 3)?;
+
+        for vertex in &mesh.vertex_info {
+            for &value in vertex.pos.iter().chain(vertex.norm.iter()).chain(vertex.tex_coords.iter()) {
+                write_float(writer, value)?;
+            }
+        }
+        for face in mesh.indices.chunks(3) {
+            writer.write_all(&[face.len() as u8])?;
+            for &i in face {
+                write_u32(writer, i)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl MeshExporter for PlyExporter {
+    fn export_to_file(&self, mesh: &Mesh, filename: &str) -> Result<(), Box<dyn Error>> {
+        let mut writer = BufWriter::new(File::create(filename)?);
+        self.write_ply(mesh, &mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
@@ -1,26 +1,170 @@
-use glium::{glutin, Display};
-use glium::glutin::WindowEvent;
+use std::rc::Rc;
+use std::f32::consts::PI;
+use std::error::Error;
+
+use glium::{Display, Frame, Surface};
+use glium::texture::SrgbTexture2d;
+use glium::glutin::{ElementState, VirtualKeyCode, WindowEvent};
+use cgmath::{Point3, Vector3};
 
 use util::types::Float;
+use util::math::clamp;
+use util::constants::SETTINGS_FILE;
+use util::frame_timer::FrameTimer;
+use config::settings::{self, Settings};
 use gl::overlay::Overlay;
+use gl::outline::Outline;
+use gl::text::GLYPH_SIZE;
 use gl::world_proxy::WorldProxy;
-use camera::free_cam::CameraState;
+use camera::free_cam::{CameraState, CameraMode};
+use hotbar::Hotbar;
+use selection::Selection;
+use model::block::{self, BlockType};
+use model::atlas::{self, TextureAtlas};
+use world::region::Region;
+use world::structure::Structure;
+use input::action::Action;
+use input::handler::InputHandler;
+use console::Console;
+use console::command::{CommandError, Dispatcher};
+use console::commands;
+use assets::AssetManager;
+use model::mesh::Mesh;
+
+const FOV_STEP: Float = 5.0;
+const OUTLINE_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.8];
+const FACE_HIGHLIGHT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.2];
+const SELECTION_OUTLINE_COLOR: [f32; 4] = [1.0, 0.6, 0.0, 0.9];
+const SELECTION_FACE_COLOR: [f32; 4] = [1.0, 0.6, 0.0, 0.15];
+const DEBUG_TEXT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const DEBUG_TEXT_POS: [Float; 2] = [4.0, 4.0];
+const DEBUG_TEXT_SCALE: Float = 2.0;
+const CONSOLE_LINES: usize = 10;
+const CONSOLE_BOTTOM: Float = 56.0;
+const CONSOLE_PADDING: Float = 4.0;
+const CONSOLE_TEXT_SCALE: Float = 2.0;
+const CONSOLE_TEXT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const CONSOLE_BACKGROUND_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.5];
+const DAY_SKY_COLOR: [Float; 3] = [66.0 / 255.0, 196.0 / 255.0, 247.0 / 255.0];
+const NIGHT_SKY_COLOR: [Float; 3] = [0.02, 0.02, 0.08];
+
+/// The block the camera is looking at.
+#[derive(Debug, Copy, Clone)]
+pub struct Target {
+    pub pos: Point3<usize>,
+    pub block_type: BlockType,
+    /// Axis and direction of the face that was hit.
+    pub face_axis: usize,
+    pub face_positive: bool,
+}
+
+impl Target {
+    /// The position of a block placed against the targeted face, if it is
+    /// inside the world.
+    pub fn place_pos(&self) -> Option<Point3<usize>> {
+        let mut new_pos = self.pos;
+        if self.face_positive {
+            new_pos[self.face_axis] += 1;
+        } else if new_pos[self.face_axis] > 0 {
+            new_pos[self.face_axis] -= 1;
+        } else {
+            return None;
+        }
+        Some(new_pos)
+    }
+}
 
 pub struct Game<'a> {
     world: WorldProxy,
     overlay: Overlay<'a>,
+    outline: Outline<'a>,
+    assets: AssetManager,
+    block_atlas: TextureAtlas,
     camera: CameraState,
+    input: InputHandler,
+    settings: Settings,
+    hotbar: Hotbar,
+    selection: Selection,
+    clipboard: Option<Structure>,
+    target: Option<Target>,
+    frame_timer: FrameTimer,
+    debug_visible: bool,
+    time_of_day: Float,
+    console: Console,
+    commands: Rc<Dispatcher<Game<'a>>>,
 }
 
 impl<'a> Game<'a> {
-    pub fn new(display: &Display) -> Self {
+    pub fn new(display: &Display, settings: Settings) -> Self {
+        let mut dispatcher = Dispatcher::new();
+        commands::register_all(&mut dispatcher);
+
+        let mut world = WorldProxy::with_capacity(display, 16);
+        world.set_history_size(settings.history_size);
+        let mut assets = AssetManager::new(&settings.resource_dirs);
+        let mut console = Console::new();
+        for name in atlas::source_names() {
+            assets.watch(&name);
+        }
+        let block_atlas = TextureAtlas::stitch(&assets, &mut console).unwrap();
+        world.set_block_uvs(block_atlas.uvs.clone());
+
         Self {
-            world: WorldProxy::with_capacity(&display, 16),
-            overlay: Overlay::new(&display),
-            camera: CameraState::new(display.get_framebuffer_dimensions()),
+            world,
+            overlay: Overlay::new(display, &assets, &mut console),
+            outline: Outline::new(display, &assets, &mut console),
+            assets,
+            block_atlas,
+            camera: CameraState::new(display.get_framebuffer_dimensions(), &settings),
+            input: InputHandler::new(settings.bindings.clone()),
+            settings,
+            hotbar: Hotbar::new(),
+            selection: Selection::new(),
+            clipboard: None,
+            target: None,
+            frame_timer: FrameTimer::new(),
+            debug_visible: false,
+            time_of_day: 12.0,
+            console,
+            commands: Rc::new(dispatcher),
         }
     }
 
+    /// The asset manager together with the console, for loading assets that
+    /// report their problems on the console.
+    pub fn assets_and_console(&mut self) -> (&mut AssetManager, &mut Console) {
+        (&mut self.assets, &mut self.console)
+    }
+
+    /// The block textures stitched together, which the world, the hotbar
+    /// and exported meshes are mapped to.
+    pub fn block_atlas(&self) -> &TextureAtlas {
+        &self.block_atlas
+    }
+
+    /// Checks the resource directories for assets that have changed and
+    /// reloads the ones that belong to the game itself, including stitching
+    /// the block atlas again. Returns the names of all changed assets, so the
+    /// caller can reload the others.
+    pub fn reload_changed_assets(&mut self) -> Vec<String> {
+        let changed = self.assets.changed_assets();
+        if !changed.is_empty() {
+            self.overlay.reload_assets(&self.assets, &changed, &mut self.console);
+            self.outline.reload_assets(&self.assets, &changed, &mut self.console);
+        }
+        if atlas::sources_changed(&changed) {
+            match TextureAtlas::stitch(&self.assets, &mut self.console) {
+                Ok(block_atlas) => {
+                    self.world.set_block_uvs(block_atlas.uvs.clone());
+                    self.block_atlas = block_atlas;
+                    self.console.print("Stitched the block textures again");
+                },
+                Err(e) => self.console.print(&format!("Failed to stitch the block textures, keeping the old atlas: {}", e)),
+            }
+        }
+        changed
+    }
+
     pub fn world(&self) -> &WorldProxy {
         &self.world
     }
@@ -29,10 +173,6 @@ impl<'a> Game<'a> {
         &mut self.world
     }
 
-    pub fn overlay(&self) -> &Overlay {
-        &self.overlay
-    }
-
     pub fn camera(&self) -> &CameraState {
         &self.camera
     }
@@ -41,45 +181,317 @@ impl<'a> Game<'a> {
         &mut self.camera
     }
 
+    pub fn hotbar_mut(&mut self) -> &mut Hotbar {
+        &mut self.hotbar
+    }
+
+    pub fn selection(&self) -> &Selection {
+        &self.selection
+    }
+
+    pub fn selection_mut(&mut self) -> &mut Selection {
+        &mut self.selection
+    }
+
+    /// The structure that was last copied, if any.
+    pub fn clipboard(&self) -> Option<&Structure> {
+        self.clipboard.as_ref()
+    }
+
+    pub fn set_clipboard(&mut self, structure: Structure) {
+        self.clipboard = Some(structure);
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    /// Changes a setting and applies it to the running game. See
+    /// ``Settings::set``.
+    pub fn set_setting(&mut self, name: &str, value: &str) -> Result<(), Box<dyn Error>> {
+        self.settings.set(name, value)?;
+        self.camera.apply_settings(&self.settings);
+        self.world.set_history_size(self.settings.history_size);
+        self.assets.set_resource_dirs(&self.settings.resource_dirs);
+        Ok(())
+    }
+
+    pub fn toggle_debug(&mut self) {
+        self.debug_visible = !self.debug_visible;
+    }
+
+    /// The time of day in hours.
+    pub fn time_of_day(&self) -> Float {
+        self.time_of_day
+    }
+
+    pub fn set_time_of_day(&mut self, hour: Float) {
+        self.time_of_day = hour % 24.0;
+    }
+
+    /// The color of the sky, which fades from day to night around 6:00 and
+    /// 18:00.
+    pub fn sky_color(&self) -> (Float, Float, Float) {
+        let sun_height = ((self.time_of_day - 6.0) / 24.0 * 2.0 * PI).sin();
+        let day = clamp((sun_height + 0.2) / 0.4, 0.0, 1.0);
+        let mix = |i: usize| NIGHT_SKY_COLOR[i] + (DAY_SKY_COLOR[i] - NIGHT_SKY_COLOR[i]) * day;
+        (mix(0), mix(1), mix(2))
+    }
+
+    /// Parses and runs a console command.
+    pub fn run_command(&mut self, line: &str) -> Result<String, CommandError> {
+        let commands = self.commands.clone();
+        commands.execute(self, line)
+    }
+
+    pub fn target(&self) -> Option<&Target> {
+        self.target.as_ref()
+    }
+
+    pub fn update(&mut self) {
+        self.frame_timer.tick();
+        self.camera.update();
+        self.target = self.find_target();
+    }
+
+    pub fn draw_outline(&self, frame: &mut Frame, mvp_matrix: [[Float; 4]; 4]) {
+        if let Some(ref target) = self.target {
+            let (min, max) = region_bounds(&Region::new(&target.pos, &target.pos));
+            self.outline.draw_box(frame, mvp_matrix, min, max, OUTLINE_COLOR);
+            self.outline.draw_face(frame, mvp_matrix, min, max, (target.face_axis, target.face_positive), FACE_HIGHLIGHT_COLOR);
+        }
+        if let Some(region) = self.selection.region() {
+            let (min, max) = region_bounds(&region);
+            self.outline.draw_box(frame, mvp_matrix, min, max, SELECTION_OUTLINE_COLOR);
+            for axis in 0..3 {
+                for &positive in [false, true].iter() {
+                    self.outline.draw_face(frame, mvp_matrix, min, max, (axis, positive), SELECTION_FACE_COLOR);
+                }
+            }
+        }
+    }
+
+    /// Draws the hotbar, console and debug text. ``texture`` is the uploaded
+    /// block atlas and ``block_mesh`` is only used for the triangle count in
+    /// the debug text.
+    pub fn draw_overlay(&mut self, frame: &mut Frame, texture: &SrgbTexture2d, block_mesh: &Mesh) {
+        if self.debug_visible {
+            let debug_text = self.debug_text(block_mesh);
+            self.overlay.draw_text(&debug_text, DEBUG_TEXT_POS, DEBUG_TEXT_COLOR, DEBUG_TEXT_SCALE);
+        }
+        if self.console.is_open() {
+            self.draw_console(frame);
+        }
+        self.overlay.draw(frame, &self.hotbar, texture, &self.block_atlas.uvs);
+    }
+
+    fn draw_console(&mut self, frame: &mut Frame) {
+        let (width, height) = frame.get_dimensions();
+        let output = self.console.output();
+        let mut text = output.iter().skip(output.len().saturating_sub(CONSOLE_LINES))
+            .fold(String::new(), |text, line| text + line + "\n");
+        text += &format!("> {}_", self.console.input());
+
+        let text_height = text.lines().count() as Float * GLYPH_SIZE * CONSOLE_TEXT_SCALE;
+        let bottom = height as Float - CONSOLE_BOTTOM;
+        let top = bottom - text_height - 2.0 * CONSOLE_PADDING;
+        self.overlay.draw_rect([0.0, top], [width as Float, bottom], CONSOLE_BACKGROUND_COLOR);
+        self.overlay.draw_text(&text, [CONSOLE_PADDING, top + CONSOLE_PADDING], CONSOLE_TEXT_COLOR, CONSOLE_TEXT_SCALE);
+    }
+
+    fn debug_text(&self, block_mesh: &Mesh) -> String {
+        let pos = self.camera.position;
+        let block_pos = (pos + Vector3::from([block::DIM / 2.0; 3])) / block::DIM;
+        let (yaw, pitch) = self.camera.yaw_pitch();
+        let facing = match ((yaw + 360.0 + 45.0) % 360.0 / 90.0) as u32 {
+            0 => "south (+z)",
+            1 => "east (+x)",
+            2 => "north (-z)",
+            _ => "west (-x)",
+        };
+        let mode = match self.camera.mode {
+            CameraMode::Fly => "fly",
+            CameraMode::Walk => "walk",
+        };
+        let target = match self.target {
+            Some(ref target) => format!("{} {} {} {} (face {}{})",
+                target.pos.x, target.pos.y, target.pos.z,
+                target.block_type.info().map(|info| info.name).unwrap_or("void"),
+                if target.face_positive { '+' } else { '-' },
+                ['x', 'y', 'z'][target.face_axis]),
+            None => "none".to_string(),
+        };
+        let selection = match self.selection.region() {
+            Some(region) => {
+                let size = region.size();
+                format!("{} {} {} to {} {} {} ({}x{}x{})",
+                    region.min.x, region.min.y, region.min.z,
+                    region.max.x, region.max.y, region.max.z,
+                    size.x, size.y, size.z)
+            },
+            None => "none".to_string(),
+        };
+        let block_count = self.world.block_count();
+        let triangle_count = block_count * block_mesh.indices.len() / 3;
+
+        format!("{:.0} fps ({:.2} ms)\n\
+                 XYZ: {:.2} / {:.2} / {:.2}\n\
+                 Block: {} / {} / {}\n\
+                 Facing: {} yaw {:.1} pitch {:.1}\n\
+                 Mode: {}\n\
+                 Target: {}\n\
+                 Selection: {}{}\n\
+                 Blocks: {} Triangles: {}",
+                self.frame_timer.fps(), self.frame_timer.frame_time_ms(),
+                pos.x, pos.y, pos.z,
+                block_pos.x.floor(), block_pos.y.floor(), block_pos.z.floor(),
+                facing, yaw, pitch,
+                mode,
+                target,
+                selection, if self.selection.tool_active() { " [tool]" } else { "" },
+                block_count, triangle_count)
+    }
+
+    fn find_target(&self) -> Option<Target> {
+        let (cam_pos, cam_dir) = (self.camera.position, self.camera.direction);
+        self.world.find_block_look_at(&cam_pos, &cam_dir, self.settings.reach_distance).map(|(block, hit_pos)| {
+            // The face that was hit is the one furthest from the block center
+            // along its axis.
+            let arr: [Float; 3] = (hit_pos - block.world_pos()).into();
+            let face_axis = (0..3).fold(0, |max_axis, axis| {
+                if arr[axis].abs() > arr[max_axis].abs() { axis } else { max_axis }
+            });
+            Target {
+                pos: *block.local_pos(),
+                block_type: block.block.get_type(),
+                face_axis,
+                face_positive: arr[face_axis] > 0.0,
+            }
+        })
+    }
+
     pub fn process_input(&mut self, event: &WindowEvent) {
-        if let &WindowEvent::MouseInput { state, button, .. } = event {
-            let (cam_pos, cam_dir) = (self.camera.position, self.camera.direction);
-            match (button, state) {
-                (glutin::MouseButton::Right, glutin::ElementState::Pressed) => {
-                    let place_block_at = self.world.find_block_look_at(&cam_pos, &cam_dir).and_then(|(block, hit_pos)| {
-                        let hit_pos = hit_pos - block.world_pos();
-                        let arr: [Float; 3] = hit_pos.into();
-                        
-                        arr.iter().position(|&val| val.abs() >= 0.99).and_then(|face_axis| {
-                            let val = arr[face_axis];
-                            if (val < 0.0 && block.local_pos()[face_axis] > 0) || val > 0.0 {
-                                let mut new_pos = *block.local_pos();
-                                if val < 0.0 { 
-                                    new_pos[face_axis] -= 1; 
-                                } else { 
-                                    new_pos[face_axis] += 1;
-                                };
-                                Some(new_pos)
-                            } else {
-                                None
-                            }
-                        })
-                    });
-                    
-                    if let Some(p) = place_block_at {
-                        self.world.add_block(&p);
-                    }
-                },
-                (glutin::MouseButton::Left, glutin::ElementState::Pressed) => {
-                    let pos_to_remove = self.world.find_block_look_at(&cam_pos, &cam_dir).and_then(|(block, _)| {
-                        Some(*block.local_pos())
-                    });
-                    if let Some(p) = pos_to_remove {
-                        self.world.remove_block(&p);
-                    }
-                },
-                _ => (),
+        if self.console.is_open() {
+            self.process_console_input(event);
+            return;
+        }
+
+        self.camera.process_input(event);
+        if let Some((action, pressed)) = self.input.translate(event) {
+            self.camera.process_action(action, pressed);
+            self.process_action(action, pressed);
+        }
+    }
+
+    fn process_console_input(&mut self, event: &WindowEvent) {
+        match *event {
+            WindowEvent::ReceivedCharacter(c) => self.console.type_char(c),
+            WindowEvent::KeyboardInput { input, .. } if input.state == ElementState::Pressed => {
+                self.console.key_pressed();
+                match input.virtual_keycode {
+                    Some(VirtualKeyCode::Return) => {
+                        if let Some(line) = self.console.submit() {
+                            let message = match self.run_command(&line) {
+                                Ok(message) => message,
+                                Err(e) => e.to_string(),
+                            };
+                            self.console.print(&message);
+                        }
+                    },
+                    Some(VirtualKeyCode::Back) => self.console.backspace(),
+                    Some(VirtualKeyCode::Escape) => self.console.close(),
+                    Some(VirtualKeyCode::Up) => self.console.browse_history(true),
+                    Some(VirtualKeyCode::Down) => self.console.browse_history(false),
+                    _ => (),
+                }
+            },
+            WindowEvent::Resized(..) => self.camera.process_input(event),
+            _ => {
+                // Releases still have to reach the camera, or it would keep
+                // moving after the console is closed.
+                if let Some((action, false)) = self.input.translate(event) {
+                    self.camera.process_action(action, false);
+                }
+            },
+        }
+    }
+
+    fn process_action(&mut self, action: Action, pressed: bool) {
+        if !pressed {
+            return;
+        }
+
+        if self.selection.tool_active() {
+            // The selection tool takes over the block editing buttons: break
+            // sets the first corner and place sets the second one.
+            let corner = match action {
+                Action::BreakBlock => Some(0),
+                Action::PlaceBlock => Some(1),
+                _ => None,
+            };
+            if let Some(index) = corner {
+                if let Some(target) = self.find_target() {
+                    self.selection.set_corner(index, target.pos);
+                }
+                return;
             }
         }
+
+        match action {
+            Action::PlaceBlock => {
+                if let Some(p) = self.find_target().and_then(|target| target.place_pos()) {
+                    self.world.add_block(&p, self.hotbar.selected_type());
+                }
+            },
+            Action::BreakBlock => {
+                if let Some(target) = self.find_target() {
+                    self.world.remove_block(&target.pos);
+                }
+            },
+            Action::PickBlock => {
+                if let Some(target) = self.find_target() {
+                    self.hotbar.set_selected_type(target.block_type);
+                }
+            },
+            Action::NextSlot => self.hotbar.select_next(),
+            Action::PreviousSlot => self.hotbar.select_previous(),
+            Action::IncreaseFov | Action::DecreaseFov => {
+                let delta = if action == Action::IncreaseFov { FOV_STEP } else { -FOV_STEP };
+                self.settings.fov = clamp(self.settings.fov + delta, settings::FOV_RANGE.0, settings::FOV_RANGE.1);
+                self.camera.apply_settings(&self.settings);
+            },
+            Action::ToggleDebug => self.toggle_debug(),
+            Action::OpenConsole => self.console.open(),
+            Action::ToggleSelectionTool => self.selection.toggle_tool(),
+            Action::Undo => {
+                if self.world.undo().is_none() {
+                    self.console.print("Nothing to undo");
+                }
+            },
+            Action::Redo => {
+                if self.world.redo().is_none() {
+                    self.console.print("Nothing to redo");
+                }
+            },
+            Action::SaveSettings => {
+                match self.settings.save(SETTINGS_FILE) {
+                    Ok(_) => println!("Saved settings to {}", SETTINGS_FILE),
+                    Err(e) => println!("Failed to save settings to {}: {}", SETTINGS_FILE, e),
+                }
+            },
+            _ => {
+                if let Some(slot) = action.slot() {
+                    self.hotbar.select(slot);
+                }
+            },
+        }
     }
 }
+
+/// The world space corners of the box around all blocks in ``region``.
+fn region_bounds(region: &Region) -> (Point3<Float>, Point3<Float>) {
+    let half = Vector3::from([block::DIM / 2.0; 3]);
+    let min = region.min.cast::<Float>() * block::DIM + (-half);
+    let max = region.max.cast::<Float>() * block::DIM + half;
+    (min, max)
+}
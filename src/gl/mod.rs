@@ -1,12 +1,24 @@
 pub mod vertex;
 pub mod overlay;
+pub mod outline;
+pub mod text;
 pub mod world_proxy;
 
+use std::io::Read;
+use std::fs::File;
+use std::error::Error;
+use std::str;
+
 use glium::{Display, IndexBuffer, VertexBuffer};
 use glium::index::PrimitiveType;
+use glium::texture::{RawImage2d, SrgbTexture2d};
+use glium::program::Program;
 
 use gl::vertex::Vertex;
 use model::mesh::Mesh;
+use assets::AssetManager;
+use console::Console;
+use util::image::Image;
 
 pub fn build_vertex_buffer(display: &Display, mesh: &Mesh) -> VertexBuffer<Vertex> {
     VertexBuffer::immutable(&display.clone(), &mesh.vertex_info).unwrap()
@@ -15,3 +27,102 @@ pub fn build_vertex_buffer(display: &Display, mesh: &Mesh) -> VertexBuffer<Verte
 pub fn build_index_buffer(display: &Display, mesh: &Mesh) -> IndexBuffer<u32> {
     IndexBuffer::immutable(&display.clone(), PrimitiveType::TrianglesList, &mesh.indices).unwrap()
 }
+
+/// Loads the png image ``name`` from ``assets`` and uploads it as a
+/// texture.
+pub fn load_texture(display: &Display, assets: &AssetManager, name: &str, console: &mut Console) -> SrgbTexture2d {
+    assets.load_with(name, |bytes, _| try_build_texture(display, bytes), console).unwrap()
+}
+
+/// Names of the vertex and fragment shader assets of the program ``name``.
+fn shader_names(name: &str) -> (String, String) {
+    (format!("shader/{}.vertex", name), format!("shader/{}.fragment", name))
+}
+
+fn try_load_program(display: &Display, assets: &AssetManager, name: &str) -> Result<Program, Box<dyn Error>> {
+    let (vertex_name, fragment_name) = shader_names(name);
+    let vertex = assets.load_string(&vertex_name)?;
+    let fragment = assets.load_string(&fragment_name)?;
+    Ok(Program::from_source(display, &vertex, &fragment, None)?)
+}
+
+/// Compiles the shaders ``shader/<name>.vertex`` and
+/// ``shader/<name>.fragment`` from ``assets`` into a program. Both shaders
+/// fall back to their built-in versions together, since an overridden shader
+/// may not fit the other one.
+pub fn load_program(display: &Display, assets: &AssetManager, name: &str, console: &mut Console) -> Program {
+    try_load_program(display, assets, name).unwrap_or_else(|e| {
+        console.print(&format!("Failed to load the {} shaders, using the built-in ones: {}", name, e));
+        let (vertex_name, fragment_name) = shader_names(name);
+        let vertex = ::assets::embedded(&vertex_name).map(str::from_utf8).unwrap().unwrap();
+        let fragment = ::assets::embedded(&fragment_name).map(str::from_utf8).unwrap().unwrap();
+        Program::from_source(display, vertex, fragment, None).unwrap()
+    })
+}
+
+/// Recompiles ``program`` if one of its shaders is among the ``changed``
+/// assets. If the new version fails to compile, the error is reported on
+/// ``console`` and the old program is kept.
+pub fn reload_program(display: &Display, assets: &AssetManager, name: &str, changed: &[String], program: &mut Program, console: &mut Console) {
+    let (vertex_name, fragment_name) = shader_names(name);
+    if !changed.iter().any(|changed_name| *changed_name == vertex_name || *changed_name == fragment_name) {
+        return;
+    }
+    match try_load_program(display, assets, name) {
+        Ok(new_program) => {
+            *program = new_program;
+            console.print(&format!("Reloaded the {} shaders", name));
+        },
+        Err(e) => console.print(&format!("Failed to reload the {} shaders, keeping the old ones: {}", name, e)),
+    }
+}
+
+/// Uploads the texture ``name`` again if it is among the ``changed``
+/// assets, keeping the old texture if the new one fails to load.
+pub fn reload_texture(display: &Display, assets: &AssetManager, name: &str, changed: &[String], texture: &mut SrgbTexture2d, console: &mut Console) {
+    if !changed.iter().any(|changed_name| changed_name == name) {
+        return;
+    }
+    match assets.build(name, |bytes, _| try_build_texture(display, bytes)) {
+        Ok(new_texture) => {
+            *texture = new_texture;
+            console.print(&format!("Reloaded {}", name));
+        },
+        Err(e) => console.print(&format!("Failed to reload {}, keeping the old version: {}", name, e)),
+    }
+}
+
+/// Uploads ``image`` as a texture, with its top row at the top of the
+/// texture coordinate range.
+pub fn build_image_texture(display: &Display, image: &Image) -> SrgbTexture2d {
+    let raw_image = RawImage2d::from_raw_rgba_reversed(&image.pixels, (image.width, image.height));
+    SrgbTexture2d::new(display, raw_image).unwrap()
+}
+
+fn try_build_texture(display: &Display, png_bytes: &[u8]) -> Result<SrgbTexture2d, Box<dyn Error>> {
+    let image = Image::decode_png(png_bytes)?;
+    let raw_image = RawImage2d::from_raw_rgba_reversed(&image.pixels, (image.width, image.height));
+    Ok(SrgbTexture2d::new(display, raw_image)?)
+}
+
+/// Loads the diffuse textures of the materials of ``mesh``, in the same
+/// order as the materials. Materials without a texture, or whose texture
+/// fails to load, get ``None``.
+pub fn build_material_textures(display: &Display, mesh: &Mesh) -> Vec<Option<SrgbTexture2d>> {
+    mesh.materials.iter().map(|material| {
+        material.diffuse_texture.as_ref().and_then(|path| {
+            let mut bytes = Vec::new();
+            let result = File::open(path)
+                .and_then(|mut f| f.read_to_end(&mut bytes))
+                .map_err(Box::<dyn Error>::from)
+                .and_then(|_| try_build_texture(display, &bytes));
+            match result {
+                Ok(texture) => Some(texture),
+                Err(e) => {
+                    println!("Failed to load texture {} of material {}: {}", path.display(), material.name, e);
+                    None
+                },
+            }
+        })
+    }).collect()
+}
@@ -0,0 +1,112 @@
+use glium::{self, Display, Frame, Surface};
+use glium::vertex::VertexBuffer;
+use glium::index::{NoIndices, PrimitiveType};
+use cgmath::Point3;
+
+use util::types::Float;
+use gl::{load_program, reload_program};
+use assets::AssetManager;
+use console::Console;
+
+#[derive(Copy, Clone)]
+struct OutlineVertex {
+    pos: [f32; 3],
+}
+
+implement_vertex!(OutlineVertex, pos);
+
+/// Offset of the outline from the box it surrounds, to keep it from
+/// z-fighting with the faces of the box.
+const OUTLINE_OFFSET: Float = 0.01;
+
+/// Draws wireframe boxes and translucent box faces in world space.
+pub struct Outline<'a> {
+    display: Display,
+    program: glium::program::Program,
+    edges: VertexBuffer<OutlineVertex>,
+    params: glium::DrawParameters<'a>,
+}
+
+/// Builds a point with coordinate ``depth`` along ``axis`` and coordinates
+/// ``u`` and ``v`` along the other two axes.
+fn axis_point(axis: usize, u: f32, v: f32, depth: f32) -> [f32; 3] {
+    match axis {
+        0 => [depth, u, v],
+        1 => [v, depth, u],
+        _ => [u, v, depth],
+    }
+}
+
+fn unit_cube_edges() -> Vec<OutlineVertex> {
+    let mut verts = Vec::new();
+    for axis in 0..3 {
+        for &(u, v) in [(0.0, 0.0), (0.0, 1.0), (1.0, 0.0), (1.0, 1.0)].iter() {
+            verts.push(OutlineVertex { pos: axis_point(axis, u, v, 0.0) });
+            verts.push(OutlineVertex { pos: axis_point(axis, u, v, 1.0) });
+        }
+    }
+    verts
+}
+
+impl<'a> Outline<'a> {
+    pub fn new(display: &Display, assets: &AssetManager, console: &mut Console) -> Self {
+        let my_display = display.clone();
+        Self {
+            program: load_program(&my_display, assets, "outline", console),
+            edges: VertexBuffer::new(&my_display, &unit_cube_edges()).unwrap(),
+            params: glium::DrawParameters {
+                depth: glium::Depth {
+                    test: glium::DepthTest::IfLessOrEqual,
+                    write: false,
+                    .. Default::default()
+                },
+                blend: glium::Blend::alpha_blending(),
+                line_width: Some(2.0),
+                .. Default::default()
+            },
+            display: my_display,
+        }
+    }
+
+    /// Reloads the shaders if they are among the ``changed`` assets.
+    pub fn reload_assets(&mut self, assets: &AssetManager, changed: &[String], console: &mut Console) {
+        reload_program(&self.display, assets, "outline", changed, &mut self.program, console);
+    }
+
+    /// Draws the edges of the box spanning from ``min`` to ``max``.
+    pub fn draw_box(&self, frame: &mut Frame, mvp_matrix: [[Float; 4]; 4], min: Point3<Float>, max: Point3<Float>, color: [f32; 4]) {
+        let uniforms = uniform! {
+            mvp_matrix: mvp_matrix,
+            offset: [min.x - OUTLINE_OFFSET, min.y - OUTLINE_OFFSET, min.z - OUTLINE_OFFSET],
+            scale: [
+                max.x - min.x + 2.0 * OUTLINE_OFFSET,
+                max.y - min.y + 2.0 * OUTLINE_OFFSET,
+                max.z - min.z + 2.0 * OUTLINE_OFFSET,
+            ],
+            color: color,
+        };
+        frame.draw(&self.edges, NoIndices(PrimitiveType::LinesList), &self.program, &uniforms, &self.params).unwrap();
+    }
+
+    /// Fills the face of the box spanning from ``min`` to ``max`` given by
+    /// ``face``, an axis and whether the face is on its positive side.
+    pub fn draw_face(&self, frame: &mut Frame, mvp_matrix: [[Float; 4]; 4], min: Point3<Float>, max: Point3<Float>, face: (usize, bool), color: [f32; 4]) {
+        let (axis, positive) = face;
+        let depth = if positive { 1.0 } else { 0.0 };
+        let corners = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+        let verts = corners.iter().map(|&(u, v)| {
+            OutlineVertex { pos: axis_point(axis, u, v, depth) }
+        }).collect::<Vec<_>>();
+        let vbuf = VertexBuffer::new(&self.display, &verts).unwrap();
+
+        let mut offset = [min.x, min.y, min.z];
+        offset[axis] += if positive { OUTLINE_OFFSET } else { -OUTLINE_OFFSET };
+        let uniforms = uniform! {
+            mvp_matrix: mvp_matrix,
+            offset: offset,
+            scale: [max.x - min.x, max.y - min.y, max.z - min.z],
+            color: color,
+        };
+        frame.draw(&vbuf, NoIndices(PrimitiveType::TrianglesList), &self.program, &uniforms, &self.params).unwrap();
+    }
+}
@@ -1,5 +1,15 @@
 use glium::{self, Display, Frame, Surface};
 use glium::vertex::VertexBuffer;
+use glium::texture::SrgbTexture2d;
+use glium::uniforms::MagnifySamplerFilter;
+
+use gl::{load_program, reload_program};
+use gl::text::TextRenderer;
+use assets::AssetManager;
+use console::Console;
+use hotbar::Hotbar;
+use model::atlas::{Face, UvTable};
+use util::types::Float;
 
 #[derive(Copy, Clone)]
 struct OverlayVertex {
@@ -15,24 +25,65 @@ const VERTICES: [OverlayVertex; 4] = [
     OverlayVertex { pos: [-1.0, 1.0] },
 ];
 
+#[derive(Copy, Clone)]
+struct HotbarVertex {
+    pos: [f32; 2],
+    tex_coords: [f32; 2],
+    color: [f32; 4],
+    textured: f32,
+}
+
+implement_vertex!(HotbarVertex, pos, tex_coords, color, textured);
+
+const SLOT_SIZE: Float = 40.0;
+const SLOT_MARGIN: Float = 4.0;
+const SLOT_BORDER: Float = 2.0;
+const SLOT_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.5];
+const SELECTED_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.9];
+const ICON_COLOR: [f32; 4] = [1.0; 4];
+const SLOT_NUMBER_INSET: Float = 2.0;
+const SLOT_NUMBER_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.8];
+
+/// Distance of the left edge of the hotbar from the left of the screen.
+fn hotbar_left(screen_width: u32, hotbar: &Hotbar) -> Float {
+    let slot_count = hotbar.slots().len() as Float;
+    let width = slot_count * SLOT_SIZE + (slot_count + 1.0) * SLOT_MARGIN;
+    (screen_width as Float - width) / 2.0
+}
+
+fn push_quad(verts: &mut Vec<HotbarVertex>, min: [Float; 2], max: [Float; 2], tex: Option<([Float; 2], [Float; 2])>, color: [f32; 4]) {
+    let (tex_min, tex_max) = tex.unwrap_or(([0.0; 2], [0.0; 2]));
+    let textured = if tex.is_some() { 1.0 } else { 0.0 };
+    let corner = |x: usize, y: usize| {
+        HotbarVertex {
+            pos: [[min[0], max[0]][x], [min[1], max[1]][y]],
+            tex_coords: [[tex_min[0], tex_max[0]][x], [tex_min[1], tex_max[1]][y]],
+            color,
+            textured,
+        }
+    };
+    verts.extend_from_slice(&[corner(0, 0), corner(1, 0), corner(1, 1), corner(0, 0), corner(1, 1), corner(0, 1)]);
+}
+
 pub struct Overlay<'a> {
     display: glium::Display,
     program: glium::program::Program, 
     ibuf: glium::index::NoIndices,
     vbuf: glium::vertex::VertexBuffer<OverlayVertex>,
     params: glium::DrawParameters<'a>,
+    hotbar_program: glium::program::Program,
+    text: TextRenderer<'a>,
+    rects: Vec<([Float; 2], [Float; 2], [f32; 4])>,
 }
 
 impl<'a> Overlay<'a> {
-    pub fn new(display: &Display) -> Self {
+    pub fn new(display: &Display, assets: &AssetManager, console: &mut Console) -> Self {
         let my_display = display.clone();
         Self {
-            program: program!(&my_display, 
-                330 => {
-                    vertex: include_str!("../shader/overlay.vertex"),
-                    fragment: include_str!("../shader/overlay.fragment"),
-                }
-            ).unwrap(),
+            program: load_program(&my_display, assets, "overlay", console),
+            hotbar_program: load_program(&my_display, assets, "hotbar", console),
+            text: TextRenderer::new(&my_display, assets, console),
+            rects: Vec::new(),
             vbuf: VertexBuffer::new(&my_display, &VERTICES).unwrap(),
             ibuf: glium::index::NoIndices(glium::index::PrimitiveType::TriangleStrip),
             params: glium::DrawParameters {
@@ -44,12 +95,86 @@ impl<'a> Overlay<'a> {
         }
     }
 
-    pub fn draw(&self, frame: &mut Frame) {
+    /// Reloads the shaders and the font if they are among the ``changed``
+    /// assets.
+    pub fn reload_assets(&mut self, assets: &AssetManager, changed: &[String], console: &mut Console) {
+        reload_program(&self.display, assets, "overlay", changed, &mut self.program, console);
+        reload_program(&self.display, assets, "hotbar", changed, &mut self.hotbar_program, console);
+        self.text.reload_assets(assets, changed, console);
+    }
+
+    /// Queues ``text`` to be drawn on top of the overlay during the next call
+    /// to ``draw``. See ``TextRenderer::queue`` for the meaning of the
+    /// arguments.
+    pub fn draw_text(&mut self, text: &str, pos: [Float; 2], color: [f32; 4], scale: Float) {
+        self.text.queue(text, pos, color, scale);
+    }
+
+    /// Queues a filled rectangle to be drawn below the text during the next
+    /// call to ``draw``. Coordinates are in pixels from the top left corner of
+    /// the screen, like those of the text.
+    pub fn draw_rect(&mut self, min: [Float; 2], max: [Float; 2], color: [f32; 4]) {
+        self.rects.push((min, max, color));
+    }
+
+    /// Draws the overlay and the queued rectangles and text. The hotbar
+    /// icons are the south faces of the blocks in the block atlas
+    /// ``texture``, as given by ``uvs``.
+    pub fn draw(&mut self, frame: &mut Frame, hotbar: &Hotbar, texture: &SrgbTexture2d, uvs: &UvTable) {
         let dim = self.display.get_framebuffer_dimensions();
         let uniforms = uniform! {
             res: [dim.0 as f32, dim.1 as f32]
         };
 
         frame.draw(&self.vbuf, self.ibuf, &self.program, &uniforms, &self.params).unwrap();
+        self.draw_hotbar(frame, hotbar, texture, uvs);
+        self.draw_slot_numbers(hotbar);
+        self.text.draw(frame);
+    }
+
+    /// Queues the number of the key that selects each hotbar slot, in the
+    /// top left corner of the slot.
+    fn draw_slot_numbers(&mut self, hotbar: &Hotbar) {
+        let (width, height) = self.display.get_framebuffer_dimensions();
+        let left = hotbar_left(width, hotbar);
+        let top = height as Float - SLOT_MARGIN - SLOT_SIZE + SLOT_NUMBER_INSET;
+        for i in 0..hotbar.slots().len() {
+            let x = left + SLOT_MARGIN + i as Float * (SLOT_SIZE + SLOT_MARGIN) + SLOT_NUMBER_INSET;
+            self.draw_text(&(i + 1).to_string(), [x, top], SLOT_NUMBER_COLOR, 1.0);
+        }
+    }
+
+    /// Draws the hotbar and the queued rectangles.
+    fn draw_hotbar(&mut self, frame: &mut Frame, hotbar: &Hotbar, texture: &SrgbTexture2d, uvs: &UvTable) {
+        let dim = self.display.get_framebuffer_dimensions();
+        let left = hotbar_left(dim.0, hotbar);
+
+        let mut verts = Vec::new();
+        for (i, slot) in hotbar.slots().iter().enumerate() {
+            let min = [left + SLOT_MARGIN + i as Float * (SLOT_SIZE + SLOT_MARGIN), SLOT_MARGIN];
+            let max = [min[0] + SLOT_SIZE, min[1] + SLOT_SIZE];
+            if i == hotbar.selected() {
+                push_quad(&mut verts, [min[0] - SLOT_BORDER, min[1] - SLOT_BORDER], [max[0] + SLOT_BORDER, max[1] + SLOT_BORDER], None, SELECTED_COLOR);
+            }
+            push_quad(&mut verts, min, max, None, SLOT_COLOR);
+            if slot.info().is_some() {
+                let icon_min = [min[0] + SLOT_MARGIN, min[1] + SLOT_MARGIN];
+                let icon_max = [max[0] - SLOT_MARGIN, max[1] - SLOT_MARGIN];
+                push_quad(&mut verts, icon_min, icon_max, Some(uvs.rect(*slot, Face::South)), ICON_COLOR);
+            }
+        }
+
+        let height = dim.1 as Float;
+        for (min, max, color) in self.rects.drain(..) {
+            push_quad(&mut verts, [min[0], height - max[1]], [max[0], height - min[1]], None, color);
+        }
+
+        let vbuf = VertexBuffer::new(&self.display, &verts).unwrap();
+        let uniforms = uniform! {
+            res: [dim.0 as f32, dim.1 as f32],
+            tex: texture.sampled().magnify_filter(MagnifySamplerFilter::Nearest),
+        };
+        let ibuf = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+        frame.draw(&vbuf, ibuf, &self.hotbar_program, &uniforms, &self.params).unwrap();
     }
 }
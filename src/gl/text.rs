@@ -0,0 +1,119 @@
+use glium::{self, Display, Frame, Surface};
+use glium::vertex::VertexBuffer;
+use glium::texture::SrgbTexture2d;
+use glium::uniforms::{MagnifySamplerFilter, MinifySamplerFilter};
+
+use gl::{load_texture, load_program, reload_texture, reload_program};
+use assets::AssetManager;
+use console::Console;
+use util::types::Float;
+
+#[derive(Copy, Clone)]
+struct TextVertex {
+    pos: [f32; 2],
+    tex_coords: [f32; 2],
+    color: [f32; 4],
+}
+
+implement_vertex!(TextVertex, pos, tex_coords, color);
+
+/// Size of a glyph in the font atlas, in pixels.
+pub const GLYPH_SIZE: Float = 8.0;
+
+/// Name of the font atlas asset.
+const FONT_ASSET: &str = "textures/font.png";
+
+/// The font atlas contains the printable ASCII characters, starting at the
+/// space character, in rows of ``ATLAS_COLUMNS`` glyphs.
+const FIRST_CHAR: u32 = 0x20;
+const LAST_CHAR: u32 = 0x7E;
+const ATLAS_COLUMNS: u32 = 16;
+const ATLAS_ROWS: u32 = 6;
+
+fn glyph_tex_rect(c: char) -> ([Float; 2], [Float; 2]) {
+    let code = c as u32;
+    let code = if (FIRST_CHAR..=LAST_CHAR).contains(&code) { code } else { '?' as u32 };
+    let index = code - FIRST_CHAR;
+    let (col, row) = ((index % ATLAS_COLUMNS) as Float, (index / ATLAS_COLUMNS) as Float);
+    let (w, h) = (1.0 / ATLAS_COLUMNS as Float, 1.0 / ATLAS_ROWS as Float);
+    ([col * w, 1.0 - (row + 1.0) * h], [(col + 1.0) * w, 1.0 - row * h])
+}
+
+/// Renders strings with a bitmap font. Strings are queued and drawn together
+/// in a single draw call.
+pub struct TextRenderer<'a> {
+    display: Display,
+    program: glium::program::Program,
+    font: SrgbTexture2d,
+    params: glium::DrawParameters<'a>,
+    verts: Vec<TextVertex>,
+}
+
+impl<'a> TextRenderer<'a> {
+    pub fn new(display: &Display, assets: &AssetManager, console: &mut Console) -> Self {
+        let my_display = display.clone();
+        Self {
+            program: load_program(&my_display, assets, "text", console),
+            font: load_texture(&my_display, assets, FONT_ASSET, console),
+            params: glium::DrawParameters {
+                blend: glium::Blend::alpha_blending(),
+                .. Default::default()
+            },
+            verts: Vec::new(),
+            display: my_display,
+        }
+    }
+
+    /// Reloads the shaders and the font if they are among the ``changed``
+    /// assets.
+    pub fn reload_assets(&mut self, assets: &AssetManager, changed: &[String], console: &mut Console) {
+        reload_program(&self.display, assets, "text", changed, &mut self.program, console);
+        reload_texture(&self.display, assets, FONT_ASSET, changed, &mut self.font, console);
+    }
+
+    /// Queues ``text`` to be drawn with its top left corner at ``pos``, in
+    /// pixels from the top left corner of the screen. Glyphs are
+    /// ``GLYPH_SIZE * scale`` pixels in size and lines are separated by ``\n``.
+    pub fn queue(&mut self, text: &str, pos: [Float; 2], color: [f32; 4], scale: Float) {
+        let size = GLYPH_SIZE * scale;
+        for (row, line) in text.lines().enumerate() {
+            for (col, c) in line.chars().enumerate() {
+                if c == ' ' {
+                    continue;
+                }
+
+                let min = [pos[0] + col as Float * size, pos[1] + row as Float * size];
+                let max = [min[0] + size, min[1] + size];
+                let (tex_min, tex_max) = glyph_tex_rect(c);
+                let corner = |x: usize, y: usize| {
+                    // Screen y grows downwards, texture y grows upwards.
+                    TextVertex {
+                        pos: [[min[0], max[0]][x], [min[1], max[1]][y]],
+                        tex_coords: [[tex_min[0], tex_max[0]][x], [tex_max[1], tex_min[1]][y]],
+                        color,
+                    }
+                };
+                self.verts.extend_from_slice(&[corner(0, 0), corner(1, 0), corner(1, 1), corner(0, 0), corner(1, 1), corner(0, 1)]);
+            }
+        }
+    }
+
+    /// Draws all queued text and clears the queue.
+    pub fn draw(&mut self, frame: &mut Frame) {
+        if self.verts.is_empty() {
+            return;
+        }
+
+        let dim = self.display.get_framebuffer_dimensions();
+        let vbuf = VertexBuffer::new(&self.display, &self.verts).unwrap();
+        let uniforms = uniform! {
+            res: [dim.0 as f32, dim.1 as f32],
+            tex: self.font.sampled()
+                .magnify_filter(MagnifySamplerFilter::Nearest)
+                .minify_filter(MinifySamplerFilter::Nearest),
+        };
+        let ibuf = glium::index::NoIndices(glium::index::PrimitiveType::TrianglesList);
+        frame.draw(&vbuf, ibuf, &self.program, &uniforms, &self.params).unwrap();
+        self.verts.clear();
+    }
+}
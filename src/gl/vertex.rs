@@ -11,13 +11,19 @@ pub struct Vertex {
 
 impl Vertex {
     pub fn new(pos: [Float; 3], tex_coords: [Float; 2], norm: [Float; 3]) -> Vertex {
-        Vertex { pos: pos, tex_coords: tex_coords, norm: norm }
+        Vertex { pos, tex_coords, norm }
     }
 }
 
+/// Instance data of a block: its position and the lower left texture
+/// coordinates of the tiles of its faces, for the positive and then the
+/// negative direction along each axis.
 #[derive(Copy, Clone)]
 pub struct Translation {
     pub translation: [Float; 3],
+    pub tex_origins_x: [Float; 4],
+    pub tex_origins_y: [Float; 4],
+    pub tex_origins_z: [Float; 4],
 }
 
 impl Ord for Translation {
@@ -62,4 +68,4 @@ impl PartialEq for Translation {
 
 
 implement_vertex!(Vertex, pos, tex_coords, norm);
-implement_vertex!(Translation, translation);
+implement_vertex!(Translation, translation, tex_origins_x, tex_origins_y, tex_origins_z);
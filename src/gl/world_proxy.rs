@@ -8,19 +8,29 @@ use glium::vertex::VertexBufferSlice;
 use gl::vertex::Translation;
 use world::world::WorldBlock;
 use model::block::{self, BlockType};
+use model::atlas::{Face, UvTable};
 use model::aabb::ray_intersect;
 use world::world::World;
-use util::constants::REACH_DISTANCE;
+use world::region::Region;
+use world::structure::Structure;
+use world::history::{ChangeSet, History};
 use util::types::Float;
 use util::math::safe_cast;
 
-fn local_pos_to_translation(p: &Point3<usize>) -> Translation {
+fn local_pos_to_translation(p: &Point3<usize>, block_type: BlockType, uvs: &UvTable) -> Translation {
+    let origins = |positive: Face, negative: Face| {
+        let (a, b) = (uvs.origin(block_type, positive), uvs.origin(block_type, negative));
+        [a[0], a[1], b[0], b[1]]
+    };
     Translation {
         translation: [
             p.x as f32 * block::DIM,
             p.y as f32 * block::DIM,
             p.z as f32 * block::DIM,
-        ]
+        ],
+        tex_origins_x: origins(Face::East, Face::West),
+        tex_origins_y: origins(Face::Top, Face::Bottom),
+        tex_origins_z: origins(Face::South, Face::North),
     }
 }
 
@@ -29,6 +39,8 @@ pub struct WorldProxy {
     translations: VertexBuffer<Translation>,
     buf_tmp: Vec<Translation>,
     update_required: bool,
+    history: History,
+    uvs: UvTable,
 }
 
 impl WorldProxy {
@@ -38,12 +50,14 @@ impl WorldProxy {
             translations: VertexBuffer::empty_dynamic(&display.clone(), capacity.pow(3)).unwrap(),
             buf_tmp: Vec::new(),
             update_required: true,
+            history: History::new(0),
+            uvs: UvTable::new(),
         }
     }
 
-    pub fn translations(&mut self) -> Option<VertexBufferSlice<Translation>> {
+    pub fn translations(&mut self) -> Option<VertexBufferSlice<'_, Translation>> {
         if let Some(slice) = self.translations.slice(..self.buf_tmp.len()) {
-            if self.update_required && self.buf_tmp.len() > 0 {
+            if self.update_required && !self.buf_tmp.is_empty() {
                 self.update_required = false;
                 slice.write(&self.buf_tmp);
             }
@@ -53,34 +67,133 @@ impl WorldProxy {
         }
     }
 
-    pub fn add_block(&mut self, pos: &Point3<usize>) {
+    /// Number of blocks that are drawn.
+    pub fn block_count(&self) -> usize {
+        self.buf_tmp.len()
+    }
+
+    pub fn add_block(&mut self, pos: &Point3<usize>, block_type: BlockType) {
+        let before = match self.world.block_type(pos) {
+            Some(before) => before,
+            None => return,
+        };
+
+        if before != block_type {
+            let mut changes = ChangeSet::new();
+            changes.push(*pos, before, block_type);
+            self.history.record(changes);
+        }
+
         self.update_required = true;
-        let t = local_pos_to_translation(pos);
+        let t = local_pos_to_translation(pos, block_type, &self.uvs);
         let search_result = self.buf_tmp.binary_search(&t);
         match search_result {
             Ok(i) => self.buf_tmp[i] = t,
             Err(i) => self.buf_tmp.insert(i, t),
         }
-        self.world.add_block(BlockType::Solid(0), pos);
+        self.world.add_block(block_type, pos);
     }
 
     pub fn remove_block(&mut self, pos: &Point3<usize>) {
         self.update_required = true;
-        let t = local_pos_to_translation(pos);
+        let t = local_pos_to_translation(pos, BlockType::Void(0), &self.uvs);
         
         if let Ok(i) = self.buf_tmp.binary_search(&t) {
+            let mut changes = ChangeSet::new();
+            changes.push(*pos, self.world.block_type(pos).unwrap(), BlockType::Void(0));
+            self.history.record(changes);
+
             self.buf_tmp.remove(i); 
             self.world.remove_block(pos);
         }
     }
 
-    pub fn find_block_look_at<'a>(&'a self, pos: &Point3<Float>, dir: &Vector3<Float>) -> Option<(WorldBlock, Point3<Float>)> {
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    /// Rebuilds the instance data of all blocks from the world, to be
+    /// uploaded at once by the next call to ``translations``.
+    fn rebuild_translations(&mut self) {
+        let uvs = &self.uvs;
+        self.buf_tmp = self.world.solid_blocks().iter()
+            .map(|&(pos, block_type)| local_pos_to_translation(&pos, block_type, uvs))
+            .collect();
+        self.update_required = true;
+    }
+
+    /// Applies a bulk edit to the world, records it in the history and
+    /// updates the instance data once afterwards. Returns the number of
+    /// blocks that changed.
+    fn edit_world<F>(&mut self, edit: F) -> usize
+        where F: FnOnce(&mut World) -> ChangeSet
+    {
+        let changes = edit(&mut self.world);
+        let count = changes.len();
+        if count > 0 {
+            self.rebuild_translations();
+            self.history.record(changes);
+        }
+        count
+    }
+
+    /// Changes where the block textures are in the texture atlas, for
+    /// example after it was stitched again.
+    pub fn set_block_uvs(&mut self, uvs: UvTable) {
+        self.uvs = uvs;
+        self.rebuild_translations();
+    }
+
+    /// Limits the number of edits that can be undone.
+    pub fn set_history_size(&mut self, size: usize) {
+        self.history.set_capacity(size);
+    }
+
+    /// Reverts the most recent edit. Returns the number of blocks that
+    /// changed, or ``None`` if there was nothing to undo.
+    pub fn undo(&mut self) -> Option<usize> {
+        self.history.undo().map(|changes| self.apply_changes(&changes))
+    }
+
+    /// Applies the most recently undone edit again. Returns the number of
+    /// blocks that changed, or ``None`` if there was nothing to redo.
+    pub fn redo(&mut self) -> Option<usize> {
+        self.history.redo().map(|changes| self.apply_changes(&changes))
+    }
+
+    fn apply_changes(&mut self, changes: &ChangeSet) -> usize {
+        self.world.apply(changes);
+        self.rebuild_translations();
+        changes.len()
+    }
+
+    pub fn fill(&mut self, region: &Region, block_type: BlockType) -> usize {
+        self.edit_world(|world| world.fill(region, block_type))
+    }
+
+    pub fn replace(&mut self, region: &Region, from: BlockType, to: BlockType) -> usize {
+        self.edit_world(|world| world.replace(region, from, to))
+    }
+
+    pub fn hollow(&mut self, region: &Region, block_type: BlockType) -> usize {
+        self.edit_world(|world| world.hollow(region, block_type))
+    }
+
+    pub fn walls(&mut self, region: &Region, block_type: BlockType) -> usize {
+        self.edit_world(|world| world.walls(region, block_type))
+    }
+
+    pub fn paste(&mut self, structure: &Structure, origin: &Point3<usize>, skip_air: bool) -> usize {
+        self.edit_world(|world| world.paste(structure, origin, skip_air))
+    }
+
+    pub fn find_block_look_at(&self, pos: &Point3<Float>, dir: &Vector3<Float>, reach: usize) -> Option<(WorldBlock<'_>, Point3<Float>)> {
         let grid_pos = safe_cast(pos) / block::DIM as usize;
-        let blocks_to_search = self.world.blocks(&grid_pos, REACH_DISTANCE);
+        let blocks_to_search = self.world.blocks(&grid_pos, reach);
         let closest_block_info = blocks_to_search.iter()
             .fold(None, |closest_block, block_info| {
-                let ray_len = (REACH_DISTANCE + 1) as f32 * block::DIM;
-                match ray_intersect(&pos, &dir, ray_len, block_info.block, &block_info.world_pos()) {
+                let ray_len = (reach + 1) as f32 * block::DIM;
+                match ray_intersect(pos, dir, ray_len, block_info.block, &block_info.world_pos()) {
                     Some((fraction, hit_pos)) => {
                         match closest_block {
                             Some((smallest_frac, _, _)) => {
@@ -98,9 +211,6 @@ impl WorldProxy {
             }
         );
 
-        match closest_block_info {
-            Some((_, hit_pos, block_info)) => Some((*block_info, hit_pos)),
-            None => None,
-        }
+        closest_block_info.map(|(_, hit_pos, block_info)| (*block_info, hit_pos))
     }
 }
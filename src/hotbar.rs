@@ -0,0 +1,50 @@
+use model::block::{BlockType, BLOCK_INFOS};
+
+pub const HOTBAR_SIZE: usize = 9;
+
+/// The block types that are at hand for placement, one of which is selected.
+#[derive(Debug)]
+pub struct Hotbar {
+    slots: [BlockType; HOTBAR_SIZE],
+    selected: usize,
+}
+
+impl Hotbar {
+    pub fn new() -> Hotbar {
+        let mut slots = [BlockType::Solid(0); HOTBAR_SIZE];
+        for (i, slot) in slots.iter_mut().enumerate() {
+            *slot = BlockType::Solid(i % BLOCK_INFOS.len());
+        }
+        Hotbar { slots, selected: 0 }
+    }
+
+    pub fn slots(&self) -> &[BlockType] {
+        &self.slots
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn selected_type(&self) -> BlockType {
+        self.slots[self.selected]
+    }
+
+    pub fn set_selected_type(&mut self, block_type: BlockType) {
+        self.slots[self.selected] = block_type;
+    }
+
+    pub fn select(&mut self, slot: usize) {
+        if slot < HOTBAR_SIZE {
+            self.selected = slot;
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % HOTBAR_SIZE;
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = (self.selected + HOTBAR_SIZE - 1) % HOTBAR_SIZE;
+    }
+}
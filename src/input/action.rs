@@ -0,0 +1,130 @@
+/// A game action that can be bound to a key or mouse button.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Action {
+    MoveForward,
+    MoveBackward,
+    MoveLeft,
+    MoveRight,
+    MoveUp,
+    MoveDown,
+    Sprint,
+    ToggleFlight,
+    PlaceBlock,
+    BreakBlock,
+    PickBlock,
+    IncreaseFov,
+    DecreaseFov,
+    SaveSettings,
+    ToggleDebug,
+    OpenConsole,
+    ToggleSelectionTool,
+    Undo,
+    Redo,
+    SelectSlot1,
+    SelectSlot2,
+    SelectSlot3,
+    SelectSlot4,
+    SelectSlot5,
+    SelectSlot6,
+    SelectSlot7,
+    SelectSlot8,
+    SelectSlot9,
+    NextSlot,
+    PreviousSlot,
+}
+
+const ALL_ACTIONS: &[Action] = &[
+    Action::MoveForward,
+    Action::MoveBackward,
+    Action::MoveLeft,
+    Action::MoveRight,
+    Action::MoveUp,
+    Action::MoveDown,
+    Action::Sprint,
+    Action::ToggleFlight,
+    Action::PlaceBlock,
+    Action::BreakBlock,
+    Action::PickBlock,
+    Action::IncreaseFov,
+    Action::DecreaseFov,
+    Action::SaveSettings,
+    Action::ToggleDebug,
+    Action::OpenConsole,
+    Action::ToggleSelectionTool,
+    Action::Undo,
+    Action::Redo,
+    Action::SelectSlot1,
+    Action::SelectSlot2,
+    Action::SelectSlot3,
+    Action::SelectSlot4,
+    Action::SelectSlot5,
+    Action::SelectSlot6,
+    Action::SelectSlot7,
+    Action::SelectSlot8,
+    Action::SelectSlot9,
+    Action::NextSlot,
+    Action::PreviousSlot,
+];
+
+impl Action {
+    pub fn all() -> &'static [Action] {
+        ALL_ACTIONS
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        action_from_name(name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        action_name(*self).unwrap()
+    }
+
+    /// The hotbar slot selected by this action, if any.
+    pub fn slot(&self) -> Option<usize> {
+        match *self {
+            Action::SelectSlot1 => Some(0),
+            Action::SelectSlot2 => Some(1),
+            Action::SelectSlot3 => Some(2),
+            Action::SelectSlot4 => Some(3),
+            Action::SelectSlot5 => Some(4),
+            Action::SelectSlot6 => Some(5),
+            Action::SelectSlot7 => Some(6),
+            Action::SelectSlot8 => Some(7),
+            Action::SelectSlot9 => Some(8),
+            _ => None,
+        }
+    }
+}
+
+name_mapping!(Action, action_from_name, action_name, {
+    MoveForward => "move_forward",
+    MoveBackward => "move_backward",
+    MoveLeft => "move_left",
+    MoveRight => "move_right",
+    MoveUp => "move_up",
+    MoveDown => "move_down",
+    Sprint => "sprint",
+    ToggleFlight => "toggle_flight",
+    PlaceBlock => "place_block",
+    BreakBlock => "break_block",
+    PickBlock => "pick_block",
+    IncreaseFov => "increase_fov",
+    DecreaseFov => "decrease_fov",
+    SaveSettings => "save_settings",
+    ToggleDebug => "toggle_debug",
+    OpenConsole => "open_console",
+    ToggleSelectionTool => "toggle_selection_tool",
+    Undo => "undo",
+    Redo => "redo",
+    SelectSlot1 => "select_slot_1",
+    SelectSlot2 => "select_slot_2",
+    SelectSlot3 => "select_slot_3",
+    SelectSlot4 => "select_slot_4",
+    SelectSlot5 => "select_slot_5",
+    SelectSlot6 => "select_slot_6",
+    SelectSlot7 => "select_slot_7",
+    SelectSlot8 => "select_slot_8",
+    SelectSlot9 => "select_slot_9",
+    NextSlot => "next_slot",
+    PreviousSlot => "previous_slot",
+});
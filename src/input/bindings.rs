@@ -0,0 +1,177 @@
+use std::error::Error;
+use std::collections::HashMap;
+
+use glium::glutin::{MouseButton, VirtualKeyCode};
+
+use config::Config;
+use input::action::Action;
+use input::keys;
+
+/// A physical input that can trigger an action.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Input {
+    Key(VirtualKeyCode),
+    Mouse(MouseButton),
+    WheelUp,
+    WheelDown,
+}
+
+impl Input {
+    pub fn from_name(name: &str) -> Option<Input> {
+        match name {
+            "WheelUp" => Some(Input::WheelUp),
+            "WheelDown" => Some(Input::WheelDown),
+            _ => keys::key_from_name(name).map(Input::Key)
+                .or_else(|| keys::mouse_button_from_name(name).map(Input::Mouse)),
+        }
+    }
+
+    pub fn name(&self) -> Option<&'static str> {
+        match *self {
+            Input::Key(key) => keys::key_name(key),
+            Input::Mouse(button) => keys::mouse_button_name(button),
+            Input::WheelUp => Some("WheelUp"),
+            Input::WheelDown => Some("WheelDown"),
+        }
+    }
+}
+
+const DEFAULT_BINDINGS: &[(Action, Input)] = &[
+    (Action::MoveForward, Input::Key(VirtualKeyCode::W)),
+    (Action::MoveBackward, Input::Key(VirtualKeyCode::S)),
+    (Action::MoveLeft, Input::Key(VirtualKeyCode::A)),
+    (Action::MoveRight, Input::Key(VirtualKeyCode::D)),
+    (Action::MoveUp, Input::Key(VirtualKeyCode::Space)),
+    (Action::MoveDown, Input::Key(VirtualKeyCode::LShift)),
+    (Action::Sprint, Input::Key(VirtualKeyCode::LControl)),
+    (Action::ToggleFlight, Input::Key(VirtualKeyCode::F)),
+    (Action::PlaceBlock, Input::Mouse(MouseButton::Right)),
+    (Action::BreakBlock, Input::Mouse(MouseButton::Left)),
+    (Action::PickBlock, Input::Mouse(MouseButton::Middle)),
+    (Action::IncreaseFov, Input::Key(VirtualKeyCode::Equals)),
+    (Action::DecreaseFov, Input::Key(VirtualKeyCode::Minus)),
+    (Action::SaveSettings, Input::Key(VirtualKeyCode::F5)),
+    (Action::ToggleDebug, Input::Key(VirtualKeyCode::F3)),
+    (Action::OpenConsole, Input::Key(VirtualKeyCode::Grave)),
+    (Action::ToggleSelectionTool, Input::Key(VirtualKeyCode::V)),
+    (Action::Undo, Input::Key(VirtualKeyCode::Z)),
+    (Action::Redo, Input::Key(VirtualKeyCode::Y)),
+    (Action::SelectSlot1, Input::Key(VirtualKeyCode::Key1)),
+    (Action::SelectSlot2, Input::Key(VirtualKeyCode::Key2)),
+    (Action::SelectSlot3, Input::Key(VirtualKeyCode::Key3)),
+    (Action::SelectSlot4, Input::Key(VirtualKeyCode::Key4)),
+    (Action::SelectSlot5, Input::Key(VirtualKeyCode::Key5)),
+    (Action::SelectSlot6, Input::Key(VirtualKeyCode::Key6)),
+    (Action::SelectSlot7, Input::Key(VirtualKeyCode::Key7)),
+    (Action::SelectSlot8, Input::Key(VirtualKeyCode::Key8)),
+    (Action::SelectSlot9, Input::Key(VirtualKeyCode::Key9)),
+    (Action::NextSlot, Input::WheelDown),
+    (Action::PreviousSlot, Input::WheelUp),
+];
+
+/// Maps physical inputs to actions. Bindings are read from the ``[bindings]``
+/// section of the settings file, where every action is assigned a
+/// comma-separated list of inputs, e.g. ``move_forward = "W, Up"``.
+#[derive(Debug, Clone)]
+pub struct Bindings {
+    map: HashMap<Input, Action>,
+}
+
+impl Bindings {
+    pub fn new() -> Bindings {
+        let mut bindings = Bindings { map: HashMap::new() };
+        for &(action, input) in DEFAULT_BINDINGS {
+            bindings.bind(input, action);
+        }
+        bindings
+    }
+
+    pub fn bind(&mut self, input: Input, action: Action) {
+        self.map.insert(input, action);
+    }
+
+    pub fn unbind(&mut self, action: Action) {
+        self.map.retain(|_, a| *a != action);
+    }
+
+    pub fn action(&self, input: &Input) -> Option<Action> {
+        self.map.get(input).cloned()
+    }
+
+    pub fn inputs(&self, action: Action) -> Vec<Input> {
+        self.map.iter().filter(|&(_, a)| *a == action).map(|(i, _)| *i).collect()
+    }
+
+    /// Applies the bindings from ``config``. Actions that are not mentioned
+    /// keep their current bindings. If any binding is invalid, nothing is
+    /// changed.
+    pub fn load(&mut self, config: &Config) -> Result<(), Box<dyn Error>> {
+        let mut new_bindings = Vec::new();
+        if let Some(section) = config.section("bindings") {
+            for (action_name, value) in section {
+                let action = Action::from_name(action_name).ok_or_else(|| {
+                    Box::<dyn Error>::from(format!("Unknown action: ``{}``", action_name))
+                })?;
+                let inputs_str = value.as_str().ok_or_else(|| {
+                    Box::<dyn Error>::from(format!("Expected a string for action ``{}``", action_name))
+                })?;
+
+                let mut inputs = Vec::new();
+                for input_name in inputs_str.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
+                    let input = Input::from_name(input_name).ok_or_else(|| {
+                        Box::<dyn Error>::from(format!("Unknown input for action ``{}``: ``{}``", action_name, input_name))
+                    })?;
+                    inputs.push(input);
+                }
+                new_bindings.push((action, inputs));
+            }
+        }
+
+        for (action, inputs) in new_bindings {
+            self.unbind(action);
+            for input in inputs {
+                self.bind(input, action);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glium::glutin::{MouseButton, VirtualKeyCode};
+
+    use config::Config;
+    use input::action::Action;
+    use super::{Bindings, Input};
+
+    #[test]
+    fn load_replaces_the_inputs_of_mentioned_actions() {
+        let mut bindings = Bindings::new();
+        let config = Config::parse("[bindings]\nmove_forward = \"Up, MouseMiddle\"\n").unwrap();
+        bindings.load(&config).unwrap();
+
+        assert_eq!(bindings.action(&Input::Key(VirtualKeyCode::Up)), Some(Action::MoveForward));
+        assert_eq!(bindings.action(&Input::Mouse(MouseButton::Middle)), Some(Action::MoveForward));
+        assert_eq!(bindings.action(&Input::Key(VirtualKeyCode::W)), None);
+        assert_eq!(bindings.action(&Input::Key(VirtualKeyCode::S)), Some(Action::MoveBackward));
+    }
+
+    #[test]
+    fn load_changes_nothing_on_error() {
+        let mut bindings = Bindings::new();
+        let config = Config::parse("[bindings]\nmove_backward = \"Down\"\nmove_forward = \"Up, NoSuchKey\"\n").unwrap();
+        assert!(bindings.load(&config).is_err());
+
+        assert_eq!(bindings.action(&Input::Key(VirtualKeyCode::W)), Some(Action::MoveForward));
+        assert_eq!(bindings.action(&Input::Key(VirtualKeyCode::S)), Some(Action::MoveBackward));
+        assert_eq!(bindings.action(&Input::Key(VirtualKeyCode::Down)), None);
+    }
+
+    #[test]
+    fn load_rejects_unknown_actions() {
+        let mut bindings = Bindings::new();
+        let config = Config::parse("[bindings]\nfly_to_the_moon = \"M\"\n").unwrap();
+        assert!(bindings.load(&config).is_err());
+    }
+}
@@ -0,0 +1,57 @@
+use std::collections::HashSet;
+
+use glium::glutin::{ElementState, MouseScrollDelta, WindowEvent};
+
+use input::action::Action;
+use input::bindings::{Bindings, Input};
+
+/// Translates window events into actions using the configured bindings.
+pub struct InputHandler {
+    bindings: Bindings,
+    held: HashSet<Input>,
+}
+
+impl InputHandler {
+    pub fn new(bindings: Bindings) -> InputHandler {
+        InputHandler {
+            bindings,
+            held: HashSet::new(),
+        }
+    }
+
+    /// Returns the action triggered by ``event`` and whether it was pressed
+    /// or released. Key repeats of inputs that are held down are ignored, and
+    /// mouse wheel scrolls only ever count as presses.
+    pub fn translate(&mut self, event: &WindowEvent) -> Option<(Action, bool)> {
+        let (input, state) = match *event {
+            WindowEvent::MouseWheel { delta, .. } => {
+                let dy = match delta {
+                    MouseScrollDelta::LineDelta(_, dy) | MouseScrollDelta::PixelDelta(_, dy) => dy,
+                };
+                let input = if dy > 0.0 {
+                    Input::WheelUp
+                } else if dy < 0.0 {
+                    Input::WheelDown
+                } else {
+                    return None;
+                };
+                return self.bindings.action(&input).map(|action| (action, true));
+            },
+            WindowEvent::KeyboardInput { input, .. } => {
+                let key = input.virtual_keycode?;
+                (Input::Key(key), input.state)
+            },
+            WindowEvent::MouseInput { state, button, .. } => (Input::Mouse(button), state),
+            _ => return None,
+        };
+
+        let pressed = state == ElementState::Pressed;
+        if pressed && !self.held.insert(input) {
+            return None;
+        } else if !pressed {
+            self.held.remove(&input);
+        }
+
+        self.bindings.action(&input).map(|action| (action, pressed))
+    }
+}
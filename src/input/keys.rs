@@ -0,0 +1,65 @@
+use glium::glutin::{MouseButton, VirtualKeyCode};
+
+name_mapping!(VirtualKeyCode, key_from_name, key_name, {
+    Key1 => "1", Key2 => "2", Key3 => "3", Key4 => "4", Key5 => "5",
+    Key6 => "6", Key7 => "7", Key8 => "8", Key9 => "9", Key0 => "0",
+
+    A => "A", B => "B", C => "C", D => "D", E => "E", F => "F", G => "G",
+    H => "H", I => "I", J => "J", K => "K", L => "L", M => "M", N => "N",
+    O => "O", P => "P", Q => "Q", R => "R", S => "S", T => "T", U => "U",
+    V => "V", W => "W", X => "X", Y => "Y", Z => "Z",
+
+    F1 => "F1", F2 => "F2", F3 => "F3", F4 => "F4", F5 => "F5", F6 => "F6",
+    F7 => "F7", F8 => "F8", F9 => "F9", F10 => "F10", F11 => "F11", F12 => "F12",
+
+    Escape => "Escape",
+    Tab => "Tab",
+    Space => "Space",
+    Return => "Return",
+    Back => "Backspace",
+    Insert => "Insert",
+    Delete => "Delete",
+    Home => "Home",
+    End => "End",
+    PageUp => "PageUp",
+    PageDown => "PageDown",
+    Left => "Left",
+    Right => "Right",
+    Up => "Up",
+    Down => "Down",
+    LShift => "LShift",
+    RShift => "RShift",
+    LControl => "LControl",
+    RControl => "RControl",
+    LAlt => "LAlt",
+    RAlt => "RAlt",
+    Grave => "Grave",
+    Minus => "Minus",
+    Equals => "Equals",
+    LBracket => "LBracket",
+    RBracket => "RBracket",
+    Semicolon => "Semicolon",
+    Apostrophe => "Apostrophe",
+    Backslash => "Backslash",
+    Comma => "Comma",
+    Period => "Period",
+    Slash => "Slash",
+});
+
+pub fn mouse_button_from_name(name: &str) -> Option<MouseButton> {
+    match name {
+        "MouseLeft" => Some(MouseButton::Left),
+        "MouseRight" => Some(MouseButton::Right),
+        "MouseMiddle" => Some(MouseButton::Middle),
+        _ => None,
+    }
+}
+
+pub fn mouse_button_name(button: MouseButton) -> Option<&'static str> {
+    match button {
+        MouseButton::Left => Some("MouseLeft"),
+        MouseButton::Right => Some("MouseRight"),
+        MouseButton::Middle => Some("MouseMiddle"),
+        MouseButton::Other(_) => None,
+    }
+}
@@ -0,0 +1,4 @@
+pub mod action;
+pub mod keys;
+pub mod bindings;
+pub mod handler;
@@ -0,0 +1,118 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::error::Error;
+
+/// An error while loading a mesh. Line and column numbers start at 1, and
+/// columns count bytes.
+#[derive(Debug)]
+pub enum LoadError {
+    Io(io::Error),
+    /// A token that could not be parsed, or a token that is missing, in
+    /// which case ``token`` is empty and ``column`` is just past the end of
+    /// the line.
+    Parse { line: usize, column: usize, token: String, message: String },
+    IndexOutOfRange { line: usize, column: usize, index: i64 },
+    UnsupportedDirective { line: usize, directive: String },
+    /// An error in a file that is referenced by the loaded file, such as a
+    /// material library.
+    Referenced { path: PathBuf, error: Box<LoadError> },
+    /// A file that is well formed but whose content does not make sense,
+    /// such as a reference to something that does not exist.
+    Invalid(String),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            LoadError::Io(ref e) => write!(f, "{}", e),
+            LoadError::Parse { line, column, ref token, ref message } => {
+                if token.is_empty() {
+                    write!(f, "line {}, column {}: {}", line, column, message)
+                } else {
+                    write!(f, "line {}, column {}: {}: ``{}``", line, column, message, token)
+                }
+            },
+            LoadError::IndexOutOfRange { line, column, index } =>
+                write!(f, "line {}, column {}: index {} is out of range", line, column, index),
+            LoadError::UnsupportedDirective { line, ref directive } =>
+                write!(f, "line {}: unsupported directive ``{}``", line, directive),
+            LoadError::Referenced { ref path, ref error } => write!(f, "in {}: {}", path.display(), error),
+            LoadError::Invalid(ref message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for LoadError {
+    fn description(&self) -> &str {
+        "failed to load mesh"
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(e: io::Error) -> LoadError {
+        LoadError::Io(e)
+    }
+}
+
+/// Column of ``token`` within ``line``, which it must be a slice of.
+pub fn column(line: &str, token: &str) -> usize {
+    token.as_ptr() as usize - line.as_ptr() as usize + 1
+}
+
+/// Splits ``reader`` into lines without failing on invalid UTF-8, which is
+/// reported with the line it occurs on instead.
+pub fn read_lines<R: io::BufRead>(mut reader: R) -> Result<Vec<String>, LoadError> {
+    let mut lines = Vec::new();
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        if reader.read_until(b'\n', &mut buf)? == 0 {
+            return Ok(lines);
+        }
+
+        match String::from_utf8(buf.clone()) {
+            Ok(line) => lines.push(line.trim_end_matches(['\n', '\r']).to_string()),
+            Err(e) => {
+                return Err(LoadError::Parse {
+                    line: lines.len() + 1,
+                    column: e.utf8_error().valid_up_to() + 1,
+                    token: String::new(),
+                    message: "invalid UTF-8".to_string(),
+                });
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::{column, read_lines, LoadError};
+
+    #[test]
+    fn columns_count_bytes_from_one() {
+        let line = "f 1 ä 3";
+        let token = line.split_whitespace().nth(3).unwrap();
+        assert_eq!(column(line, token), 8);
+        assert_eq!(column(line, &line[..1]), 1);
+    }
+
+    #[test]
+    fn reads_lines_and_reports_invalid_utf8() {
+        let lines = read_lines(Cursor::new(&b"a\r\nb\n\nc"[..])).unwrap();
+        assert_eq!(lines, vec!["a", "b", "", "c"]);
+
+        match read_lines(Cursor::new(&b"v 1 2 3\nv 1 \xff 3\n"[..])) {
+            Err(LoadError::Parse { line: 2, column: 5, .. }) => (),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn displays_the_position_and_token() {
+        let error = LoadError::Parse { line: 3, column: 7, token: "x".to_string(), message: "invalid coordinate".to_string() };
+        assert_eq!(error.to_string(), "line 3, column 7: invalid coordinate: ``x``");
+    }
+}
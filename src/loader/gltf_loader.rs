@@ -0,0 +1,708 @@
+use std::mem;
+use std::io::Read;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use cgmath::{Matrix, Matrix4, Quaternion, SquareMatrix, Vector3, Vector4, InnerSpace};
+
+use util::types::{Float, GLIndex};
+use model::mesh::{Mesh, NormalMode};
+use model::material::Material;
+use loader::MeshLoader;
+use loader::error::LoadError;
+use loader::json::{self, Json};
+use gl::vertex::Vertex;
+
+const GLB_MAGIC: &[u8; 4] = b"glTF";
+const GLB_VERSION: u32 = 2;
+const CHUNK_JSON: u32 = 0x4e4f534a;
+const CHUNK_BIN: u32 = 0x004e4942;
+
+const MODE_TRIANGLES: usize = 4;
+const MODE_TRIANGLE_STRIP: usize = 5;
+const MODE_TRIANGLE_FAN: usize = 6;
+
+const BYTE: usize = 5120;
+const UNSIGNED_BYTE: usize = 5121;
+const SHORT: usize = 5122;
+const UNSIGNED_SHORT: usize = 5123;
+const UNSIGNED_INT: usize = 5125;
+const FLOAT: usize = 5126;
+
+/// Most values a single accessor may have, so that a bogus ``count`` cannot
+/// make us allocate arbitrary amounts of memory.
+pub const MAX_ACCESSOR_VALUES: usize = 1 << 26;
+
+fn invalid(message: String) -> LoadError {
+    LoadError::Invalid(message)
+}
+
+fn read_u32(bytes: &[u8], pos: usize) -> Result<u32, LoadError> {
+    match bytes.get(pos..pos + 4) {
+        Some(b) => Ok(b[0] as u32 | (b[1] as u32) << 8 | (b[2] as u32) << 16 | (b[3] as u32) << 24),
+        None => Err(invalid("unexpected end of .glb file".to_string())),
+    }
+}
+
+/// Decodes standard base64, ignoring padding.
+fn decode_base64(text: &str) -> Result<Vec<u8>, LoadError> {
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let mut bits = 0u32;
+    let mut bit_count = 0;
+    for c in text.bytes().take_while(|&c| c != b'=') {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return Err(invalid(format!("invalid character in base64 data: ``{}``", c as char))),
+        };
+        bits = bits << 6 | value as u32;
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            bytes.push((bits >> bit_count) as u8);
+        }
+    }
+    Ok(bytes)
+}
+
+/// Element ``index`` of the top-level array ``list``, such as a mesh or an
+/// accessor.
+fn element<'a>(root: &'a Json, list: &str, index: usize) -> Result<&'a Json, LoadError> {
+    root.get(list).and_then(|values| values.as_array()).and_then(|values| values.get(index))
+        .ok_or_else(|| invalid(format!("{} {} does not exist", list, index)))
+}
+
+/// The optional index or count ``key`` of ``value``.
+fn index_field(value: &Json, key: &str) -> Result<Option<usize>, LoadError> {
+    match value.get(key) {
+        Some(field) => field.as_usize().map(Some).ok_or_else(|| invalid(format!("``{}`` must be a non-negative integer", key))),
+        None => Ok(None),
+    }
+}
+
+fn required_index_field(value: &Json, key: &str, what: &str) -> Result<usize, LoadError> {
+    index_field(value, key)?.ok_or_else(|| invalid(format!("{} without ``{}``", what, key)))
+}
+
+/// The array of numbers ``key`` of ``value``, if it has ``len`` of them.
+fn numbers_field(value: &Json, key: &str, len: usize) -> Result<Option<Vec<Float>>, LoadError> {
+    match value.get(key) {
+        Some(field) => {
+            let numbers = field.as_array().unwrap_or(&[]).iter().filter_map(|n| n.as_f64()).map(|n| n as Float).collect::<Vec<_>>();
+            if numbers.len() != len {
+                return Err(invalid(format!("``{}`` must be an array of {} numbers", key, len)));
+            }
+            Ok(Some(numbers))
+        },
+        None => Ok(None),
+    }
+}
+
+/// Number of components of an accessor ``type``.
+fn component_count(type_name: &str) -> Option<usize> {
+    match type_name {
+        "SCALAR" => Some(1),
+        "VEC2" => Some(2),
+        "VEC3" => Some(3),
+        "VEC4" | "MAT2" => Some(4),
+        "MAT3" => Some(9),
+        "MAT4" => Some(16),
+        _ => None,
+    }
+}
+
+/// Reads a little endian component and, for normalized integers, maps it to
+/// ``[0, 1]`` or ``[-1, 1]``.
+fn read_component(bytes: &[u8], component_type: usize, normalized: bool) -> f64 {
+    let u16_at = |b: &[u8]| b[0] as u16 | (b[1] as u16) << 8;
+    let (value, max) = match component_type {
+        BYTE => (bytes[0] as i8 as f64, 127.0),
+        UNSIGNED_BYTE => (bytes[0] as f64, 255.0),
+        SHORT => (u16_at(bytes) as i16 as f64, 32767.0),
+        UNSIGNED_SHORT => (u16_at(bytes) as f64, 65535.0),
+        UNSIGNED_INT => {
+            let bits = bytes[0] as u32 | (bytes[1] as u32) << 8 | (bytes[2] as u32) << 16 | (bytes[3] as u32) << 24;
+            (bits as f64, 4294967295.0)
+        },
+        _ => {
+            let bits = bytes[0] as u32 | (bytes[1] as u32) << 8 | (bytes[2] as u32) << 16 | (bytes[3] as u32) << 24;
+            return f32::from_bits(bits) as f64;
+        },
+    };
+    if normalized { (value / max).max(-1.0) } else { value }
+}
+
+/// The parts of a glTF file that meshes are built from.
+struct Document {
+    root: Json,
+    buffers: Vec<Vec<u8>>,
+    materials: Vec<Material>,
+}
+
+impl Document {
+    /// Reads the values of accessor ``index`` as a flat list, checking that
+    /// its type is ``type_name``.
+    fn read_accessor(&self, index: usize, type_name: &str) -> Result<Vec<f64>, LoadError> {
+        let accessor = element(&self.root, "accessors", index)?;
+        if accessor.get("type").and_then(|t| t.as_str()) != Some(type_name) {
+            return Err(invalid(format!("accessor {} must be of type ``{}``", index, type_name)));
+        }
+        if accessor.get("sparse").is_some() {
+            return Err(invalid(format!("accessor {} is sparse, which is not supported", index)));
+        }
+
+        let components = component_count(type_name).unwrap();
+        let count = required_index_field(accessor, "count", "accessor")?;
+        let component_type = required_index_field(accessor, "componentType", "accessor")?;
+        let component_size = match component_type {
+            BYTE | UNSIGNED_BYTE => 1,
+            SHORT | UNSIGNED_SHORT => 2,
+            UNSIGNED_INT | FLOAT => 4,
+            _ => return Err(invalid(format!("accessor {} has unknown component type {}", index, component_type))),
+        };
+        let normalized = accessor.get("normalized").and_then(|n| n.as_bool()).unwrap_or(false);
+        let value_count = match count.checked_mul(components) {
+            Some(value_count) if value_count <= MAX_ACCESSOR_VALUES => value_count,
+            _ => return Err(invalid(format!("accessor {} has too many elements: {}", index, count))),
+        };
+
+        // Accessors without a buffer view are all zeros.
+        let view_index = match index_field(accessor, "bufferView")? {
+            Some(view_index) => view_index,
+            None => return Ok(vec![0.0; value_count]),
+        };
+        let view = element(&self.root, "bufferViews", view_index)?;
+        let buffer_index = required_index_field(view, "buffer", "buffer view")?;
+        let buffer = self.buffers.get(buffer_index)
+            .ok_or_else(|| invalid(format!("buffers {} does not exist", buffer_index)))?;
+        let view_start = index_field(view, "byteOffset")?.unwrap_or(0);
+        let view_len = required_index_field(view, "byteLength", "buffer view")?;
+        let element_size = components * component_size;
+        let stride = index_field(view, "byteStride")?.unwrap_or(element_size);
+        let start = index_field(accessor, "byteOffset")?.unwrap_or(0);
+
+        let data = view_start.checked_add(view_len).and_then(|view_end| buffer.get(view_start..view_end))
+            .ok_or_else(|| invalid(format!("buffer view {} exceeds its buffer", view_index)))?;
+        let end = (count.max(1) - 1).checked_mul(stride)
+            .and_then(|offset| offset.checked_add(start))
+            .and_then(|offset| offset.checked_add(element_size));
+        if count > 0 && end.map(|end| end > data.len()).unwrap_or(true) {
+            return Err(invalid(format!("accessor {} exceeds its buffer view", index)));
+        }
+
+        let mut values = Vec::with_capacity(value_count);
+        for i in 0..count {
+            for c in 0..components {
+                let pos = start + i * stride + c * component_size;
+                values.push(read_component(&data[pos..pos + component_size], component_type, normalized));
+            }
+        }
+        Ok(values)
+    }
+
+    /// Adds the triangles of every primitive of mesh ``index`` to ``mesh``,
+    /// with each primitive in its own submesh. Primitives that are not made
+    /// of triangles are skipped.
+    fn add_mesh(&self, index: usize, transform: &Matrix4<Float>, mesh: &mut Mesh) -> Result<(), LoadError> {
+        let gltf_mesh = element(&self.root, "meshes", index)?;
+        let primitives = gltf_mesh.get("primitives").and_then(|p| p.as_array())
+            .ok_or_else(|| invalid(format!("mesh {} has no primitives", index)))?;
+        let normal_transform = transform.invert().unwrap_or(Matrix4::identity()).transpose();
+        // Transforms that mirror the mesh also reverse the winding order.
+        let mirrored = transform.determinant() < 0.0;
+
+        for primitive in primitives {
+            let mode = index_field(primitive, "mode")?.unwrap_or(MODE_TRIANGLES);
+            if mode != MODE_TRIANGLES && mode != MODE_TRIANGLE_STRIP && mode != MODE_TRIANGLE_FAN {
+                continue;
+            }
+
+            let attributes = primitive.get("attributes")
+                .ok_or_else(|| invalid(format!("a primitive of mesh {} has no attributes", index)))?;
+            let position_accessor = required_index_field(attributes, "POSITION", "primitive")?;
+            let positions = self.read_accessor(position_accessor, "VEC3")?;
+            let vertex_count = positions.len() / 3;
+            let normals = match index_field(attributes, "NORMAL")? {
+                Some(accessor) => Some(self.read_accessor(accessor, "VEC3")?),
+                None => None,
+            };
+            let tex_coords = match index_field(attributes, "TEXCOORD_0")? {
+                Some(accessor) => Some(self.read_accessor(accessor, "VEC2")?),
+                None => None,
+            };
+            if normals.as_ref().map(|n| n.len() != positions.len()).unwrap_or(false) ||
+                tex_coords.as_ref().map(|t| t.len() / 2 != vertex_count).unwrap_or(false) {
+                return Err(invalid(format!("the attributes of a primitive of mesh {} differ in length", index)));
+            }
+
+            let indices = match index_field(primitive, "indices")? {
+                Some(accessor) => self.read_accessor(accessor, "SCALAR")?.into_iter().map(|i| i as usize).collect(),
+                None => (0..vertex_count).collect::<Vec<usize>>(),
+            };
+            if let Some(&i) = indices.iter().find(|&&i| i >= vertex_count) {
+                return Err(invalid(format!("index {} of a primitive of mesh {} is out of range", i, index)));
+            }
+
+            let base = mesh.vertex_info.len() as GLIndex;
+            for i in 0..vertex_count {
+                let p = transform * Vector4::new(positions[i * 3] as Float, positions[i * 3 + 1] as Float, positions[i * 3 + 2] as Float, 1.0);
+                let norm = match normals {
+                    Some(ref n) => {
+                        let n = (normal_transform * Vector4::new(n[i * 3] as Float, n[i * 3 + 1] as Float, n[i * 3 + 2] as Float, 0.0)).truncate();
+                        if n.magnitude2() > 0.0 { n.normalize().into() } else { [0.0; 3] }
+                    },
+                    None => [0.0; 3],
+                };
+                // glTF puts the origin of texture coordinates at the top left.
+                let tex = match tex_coords {
+                    Some(ref t) => [t[i * 2] as Float, 1.0 - t[i * 2 + 1] as Float],
+                    None => [0.0; 2],
+                };
+                mesh.vertex_info.push(Vertex::new([p.x, p.y, p.z], tex, norm));
+            }
+
+            let material = index_field(primitive, "material")?;
+            if material.map(|m| m >= self.materials.len()).unwrap_or(false) {
+                return Err(invalid(format!("a primitive of mesh {} refers to a material that does not exist", index)));
+            }
+            mesh.begin_submesh(material);
+            let triangle_count = match mode {
+                MODE_TRIANGLES => indices.len() / 3,
+                _ => if indices.len() >= 3 { indices.len() - 2 } else { 0 },
+            };
+            for t in 0..triangle_count {
+                let mut triangle = match mode {
+                    MODE_TRIANGLES => [indices[t * 3], indices[t * 3 + 1], indices[t * 3 + 2]],
+                    // Every other triangle of a strip is flipped to keep the
+                    // winding order the same.
+                    MODE_TRIANGLE_STRIP if t % 2 == 1 => [indices[t + 1], indices[t], indices[t + 2]],
+                    MODE_TRIANGLE_STRIP => [indices[t], indices[t + 1], indices[t + 2]],
+                    _ => [indices[0], indices[t + 1], indices[t + 2]],
+                };
+                if mirrored {
+                    triangle.swap(1, 2);
+                }
+                mesh.indices.extend(triangle.iter().map(|&i| base + i as GLIndex));
+            }
+        }
+        mesh.end_submesh();
+        Ok(())
+    }
+
+    /// Adds the meshes of node ``index`` and its descendants to ``mesh``.
+    /// ``visited`` has an entry for every node, set once it has been added.
+    fn add_node(&self, index: usize, parent_transform: &Matrix4<Float>, visited: &mut [bool], mesh: &mut Mesh) -> Result<(), LoadError> {
+        let node = element(&self.root, "nodes", index)?;
+        // Nodes form a tree, so a node that is reached twice either has
+        // several parents or is part of a cycle.
+        if mem::replace(&mut visited[index], true) {
+            return Err(invalid(format!("node {} is reached more than once", index)));
+        }
+
+        let transform = parent_transform * node_transform(node)?;
+        if let Some(mesh_index) = index_field(node, "mesh")? {
+            self.add_mesh(mesh_index, &transform, mesh)?;
+        }
+        for child in node.get("children").and_then(|c| c.as_array()).unwrap_or(&[]) {
+            let child = child.as_usize().ok_or_else(|| invalid(format!("node {} has an invalid child", index)))?;
+            self.add_node(child, &transform, visited, mesh)?;
+        }
+        Ok(())
+    }
+
+    /// Generates flat normals for the vertices of the primitives that have
+    /// none once ``mesh`` is complete. Normals from the file are kept.
+    /// Primitives without indices repeat shared vertices for every
+    /// triangle, so equal vertices are merged afterwards.
+    fn finish_mesh(&self, mesh: &mut Mesh) {
+        if mesh.has_missing_normals() {
+            mesh.compute_missing_normals(NormalMode::Flat);
+        }
+        mesh.weld_vertices(0.0);
+    }
+
+    fn new_mesh(&self) -> Mesh {
+        let mut mesh = Mesh::new();
+        mesh.materials = self.materials.clone();
+        mesh
+    }
+}
+
+/// The local transform of a node, from either its matrix or its
+/// translation, rotation and scale.
+fn node_transform(node: &Json) -> Result<Matrix4<Float>, LoadError> {
+    if let Some(m) = numbers_field(node, "matrix", 16)? {
+        return Ok(Matrix4::new(m[0], m[1], m[2], m[3], m[4], m[5], m[6], m[7],
+                               m[8], m[9], m[10], m[11], m[12], m[13], m[14], m[15]));
+    }
+
+    let t = numbers_field(node, "translation", 3)?.unwrap_or(vec![0.0; 3]);
+    let r = numbers_field(node, "rotation", 4)?.unwrap_or(vec![0.0, 0.0, 0.0, 1.0]);
+    let s = numbers_field(node, "scale", 3)?.unwrap_or(vec![1.0; 3]);
+    Ok(Matrix4::from_translation(Vector3::new(t[0], t[1], t[2])) *
+       Matrix4::from(Quaternion::new(r[3], r[0], r[1], r[2])) *
+       Matrix4::from_nonuniform_scale(s[0], s[1], s[2]))
+}
+
+/// Loads glTF 2.0 models, either as ``.gltf`` files with separate or
+/// embedded buffers, or as binary ``.glb`` files.
+///
+/// Positions, normals, the first set of texture coordinates and indices are
+/// read, as well as the base color and texture of materials. Every primitive
+/// becomes a submesh. Primitives without normals get flat normals, as the
+/// specification asks for.
+pub struct GltfLoader {
+    base_dir: PathBuf,
+}
+
+impl GltfLoader {
+    pub fn new() -> GltfLoader {
+        GltfLoader { base_dir: PathBuf::new() }
+    }
+
+    /// Splits a ``.glb`` file into its JSON and binary chunks.
+    fn read_glb(bytes: &[u8]) -> Result<(Json, Option<Vec<u8>>), LoadError> {
+        let version = read_u32(bytes, 4)?;
+        if version != GLB_VERSION {
+            return Err(invalid(format!("unsupported .glb version {}", version)));
+        }
+        let len = (read_u32(bytes, 8)? as usize).min(bytes.len());
+
+        let mut root = None;
+        let mut bin = None;
+        let mut pos = 12;
+        while pos < len {
+            let chunk_len = read_u32(bytes, pos)? as usize;
+            let chunk_type = read_u32(bytes, pos + 4)?;
+            let chunk = bytes.get(pos + 8..pos + 8 + chunk_len)
+                .ok_or_else(|| invalid("chunk exceeds the end of the .glb file".to_string()))?;
+            match chunk_type {
+                CHUNK_JSON if root.is_none() => root = Some(json::parse_bytes(chunk)?),
+                CHUNK_BIN if bin.is_none() => bin = Some(chunk.to_vec()),
+                _ => (),
+            }
+            pos += 8 + chunk_len;
+        }
+        let root = root.ok_or_else(|| invalid("no JSON chunk in .glb file".to_string()))?;
+        Ok((root, bin))
+    }
+
+    /// Reads the buffers of ``root``. Buffers are either data URIs, files
+    /// relative to the base directory, or the binary chunk of a ``.glb``
+    /// file.
+    fn read_buffers(&self, root: &Json, mut bin: Option<Vec<u8>>) -> Result<Vec<Vec<u8>>, LoadError> {
+        let mut buffers = Vec::new();
+        for (i, buffer) in root.get("buffers").and_then(|b| b.as_array()).unwrap_or(&[]).iter().enumerate() {
+            let data = match buffer.get("uri").and_then(|uri| uri.as_str()) {
+                Some(uri) if uri.starts_with("data:") => {
+                    let start = uri.find(";base64,")
+                        .ok_or_else(|| invalid(format!("buffer {} is a data URI that is not base64", i)))?;
+                    decode_base64(&uri[start + ";base64,".len()..])?
+                },
+                Some(uri) => {
+                    let path = self.base_dir.join(uri);
+                    let mut data = Vec::new();
+                    File::open(&path).and_then(|mut f| f.read_to_end(&mut data))
+                        .map_err(|e| LoadError::Referenced { path: path.clone(), error: Box::new(LoadError::Io(e)) })?;
+                    data
+                },
+                None if i == 0 => bin.take().ok_or_else(|| invalid("buffer 0 has no URI and there is no binary chunk".to_string()))?,
+                None => return Err(invalid(format!("buffer {} has no URI", i))),
+            };
+
+            let len = required_index_field(buffer, "byteLength", "buffer")?;
+            if data.len() < len {
+                return Err(invalid(format!("buffer {} is shorter than its byteLength", i)));
+            }
+            buffers.push(data);
+        }
+        Ok(buffers)
+    }
+
+    /// Reads the materials of ``root``. Only textures with an image file are
+    /// used, since materials refer to their textures by path.
+    fn read_materials(&self, root: &Json) -> Vec<Material> {
+        let materials = root.get("materials").and_then(|m| m.as_array()).unwrap_or(&[]);
+        materials.iter().enumerate().map(|(i, gltf_material)| {
+            let name = gltf_material.get("name").and_then(|n| n.as_str()).map(|n| n.to_string())
+                .unwrap_or_else(|| format!("material{}", i));
+            let mut material = Material::new(&name);
+            let pbr = match gltf_material.get("pbrMetallicRoughness") {
+                Some(pbr) => pbr,
+                None => return material,
+            };
+            if let Ok(Some(color)) = numbers_field(pbr, "baseColorFactor", 4) {
+                material.diffuse_color = [color[0], color[1], color[2]];
+            }
+            material.diffuse_texture = pbr.get("baseColorTexture")
+                .and_then(|t| t.get("index")).and_then(|t| t.as_usize())
+                .and_then(|t| element(root, "textures", t).ok())
+                .and_then(|t| t.get("source")).and_then(|s| s.as_usize())
+                .and_then(|s| element(root, "images", s).ok())
+                .and_then(|image| image.get("uri")).and_then(|uri| uri.as_str())
+                .and_then(|uri| if uri.starts_with("data:") { None } else { Some(self.base_dir.join(uri)) });
+            material
+        }).collect()
+    }
+
+    fn read_document(&self, bytes: &[u8]) -> Result<Document, LoadError> {
+        let (root, bin) = if bytes.starts_with(GLB_MAGIC) {
+            GltfLoader::read_glb(bytes)?
+        } else {
+            (json::parse_bytes(bytes)?, None)
+        };
+
+        let version = root.get("asset").and_then(|asset| asset.get("version")).and_then(|v| v.as_str()).unwrap_or("");
+        if !version.starts_with("2.") {
+            return Err(invalid(format!("unsupported glTF version ``{}``", version)));
+        }
+
+        let buffers = self.read_buffers(&root, bin)?;
+        let materials = self.read_materials(&root);
+        Ok(Document { root, buffers, materials })
+    }
+
+    /// Merges the meshes of the default scene into one mesh, with the
+    /// transforms of their nodes applied. Files without scenes have all of
+    /// their meshes merged as they are.
+    fn load_mesh(&self, bytes: &[u8]) -> Result<Mesh, LoadError> {
+        let document = self.read_document(bytes)?;
+        let root = &document.root;
+        let mut mesh = document.new_mesh();
+
+        let scene = match index_field(root, "scene")? {
+            Some(scene) => Some(element(root, "scenes", scene)?),
+            None => root.get("scenes").and_then(|s| s.as_array()).and_then(|s| s.first()),
+        };
+        match scene {
+            Some(scene) => {
+                let node_count = root.get("nodes").and_then(|n| n.as_array()).map(|n| n.len()).unwrap_or(0);
+                let mut visited = vec![false; node_count];
+                for node in scene.get("nodes").and_then(|n| n.as_array()).unwrap_or(&[]) {
+                    let node = node.as_usize().ok_or_else(|| invalid("a scene has an invalid node".to_string()))?;
+                    document.add_node(node, &Matrix4::identity(), &mut visited, &mut mesh)?;
+                }
+            },
+            None => {
+                let mesh_count = root.get("meshes").and_then(|m| m.as_array()).map(|m| m.len()).unwrap_or(0);
+                for i in 0..mesh_count {
+                    document.add_mesh(i, &Matrix4::identity(), &mut mesh)?;
+                }
+            },
+        }
+        document.finish_mesh(&mut mesh);
+        Ok(mesh)
+    }
+
+    /// Loads every mesh of the file without node transforms, named after
+    /// the mesh, or ``mesh`` followed by its index if it has no name.
+    fn load_meshes(&self, bytes: &[u8]) -> Result<Vec<(String, Mesh)>, LoadError> {
+        let document = self.read_document(bytes)?;
+        let gltf_meshes = document.root.get("meshes").and_then(|m| m.as_array()).unwrap_or(&[]);
+        let mut meshes = Vec::with_capacity(gltf_meshes.len());
+        for (i, gltf_mesh) in gltf_meshes.iter().enumerate() {
+            let name = gltf_mesh.get("name").and_then(|n| n.as_str()).map(|n| n.to_string())
+                .unwrap_or_else(|| format!("mesh{}", i));
+            let mut mesh = document.new_mesh();
+            document.add_mesh(i, &Matrix4::identity(), &mut mesh)?;
+            document.finish_mesh(&mut mesh);
+            meshes.push((name, mesh));
+        }
+        Ok(meshes)
+    }
+
+    fn read_file(&mut self, filename: &str) -> Result<Vec<u8>, LoadError> {
+        let mut bytes = Vec::new();
+        File::open(filename)?.read_to_end(&mut bytes)?;
+        self.base_dir = Path::new(filename).parent().map(|dir| dir.to_path_buf()).unwrap_or_default();
+        Ok(bytes)
+    }
+}
+
+impl MeshLoader for GltfLoader {
+    /// External buffers and images are looked up relative to the working
+    /// directory.
+    fn load_from_str(&mut self, mesh_str: &str) -> Result<Mesh, LoadError> {
+        self.base_dir = PathBuf::new();
+        self.load_mesh(mesh_str.as_bytes())
+    }
+
+    /// Loads ``.gltf`` as well as ``.glb`` files. External buffers and
+    /// images are looked up next to the file.
+    fn load_from_file(&mut self, filename: &str) -> Result<Mesh, LoadError> {
+        let bytes = self.read_file(filename)?;
+        self.load_mesh(&bytes)
+    }
+
+    fn load_named_from_str(&mut self, mesh_str: &str) -> Result<Vec<(String, Mesh)>, LoadError> {
+        self.base_dir = PathBuf::new();
+        self.load_meshes(mesh_str.as_bytes())
+    }
+
+    fn load_named_from_file(&mut self, filename: &str) -> Result<Vec<(String, Mesh)>, LoadError> {
+        let bytes = self.read_file(filename)?;
+        self.load_meshes(&bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Vector3, InnerSpace};
+
+    use util::types::Float;
+    use model::mesh::Mesh;
+    use loader::MeshLoader;
+    use loader::error::LoadError;
+    use super::{GltfLoader, decode_base64};
+
+    const BASE64: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    /// A data URI holding ``values`` as little endian floats.
+    fn data_uri(values: &[f32]) -> String {
+        let bytes = values.iter().flat_map(|v| {
+            let bits = v.to_bits();
+            vec![bits as u8, (bits >> 8) as u8, (bits >> 16) as u8, (bits >> 24) as u8]
+        }).collect::<Vec<u8>>();
+        let mut uri = "data:application/octet-stream;base64,".to_string();
+        for chunk in bytes.chunks(3) {
+            let bits = (chunk[0] as u32) << 16 | (*chunk.get(1).unwrap_or(&0) as u32) << 8 | *chunk.get(2).unwrap_or(&0) as u32;
+            for i in 0..chunk.len() + 1 {
+                uri.push(BASE64[(bits >> (18 - 6 * i) & 63) as usize] as char);
+            }
+        }
+        uri
+    }
+
+    /// A glTF document with one buffer holding the four corners of the unit
+    /// square in the XY plane, followed by three normals. Accessor 0 covers
+    /// the first three corners, accessor 1 all four and accessor 2 the
+    /// normals.
+    fn document(meshes: &str, nodes: &str) -> String {
+        let tilted = [0.6, 0.0, 0.8];
+        let mut values = vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0];
+        for _ in 0..3 {
+            values.extend_from_slice(&tilted);
+        }
+        format!(r#"{{
+            "asset": {{ "version": "2.0" }},
+            "buffers": [ {{ "uri": "{}", "byteLength": 84 }} ],
+            "bufferViews": [
+                {{ "buffer": 0, "byteLength": 48 }},
+                {{ "buffer": 0, "byteOffset": 48, "byteLength": 36 }}
+            ],
+            "accessors": [
+                {{ "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3" }},
+                {{ "bufferView": 0, "componentType": 5126, "count": 4, "type": "VEC3" }},
+                {{ "bufferView": 1, "componentType": 5126, "count": 3, "type": "VEC3" }}
+            ],
+            "meshes": [ {} ],
+            "nodes": [ {} ],
+            "scenes": [ {{ "nodes": [0] }} ]
+        }}"#, data_uri(&values), meshes, nodes)
+    }
+
+    fn load(meshes: &str, nodes: &str) -> Result<Mesh, LoadError> {
+        GltfLoader::new().load_from_str(&document(meshes, nodes))
+    }
+
+    /// The normals of the triangles following their winding order.
+    fn face_normals(mesh: &Mesh) -> Vec<Vector3<Float>> {
+        mesh.indices.chunks(3).map(|t| {
+            let p = |i: u32| { let p = mesh.vertex_info[i as usize].pos; Vector3::new(p[0], p[1], p[2]) };
+            (p(t[1]) - p(t[0])).cross(p(t[2]) - p(t[0])).normalize()
+        }).collect()
+    }
+
+    #[test]
+    fn loads_a_triangle_from_a_data_uri() {
+        let mesh = load(r#"{ "primitives": [ { "attributes": { "POSITION": 0 } } ] }"#, r#"{ "mesh": 0 }"#).unwrap();
+        assert_eq!(mesh.indices.len(), 3);
+        assert_eq!(mesh.vertex_info[mesh.indices[1] as usize].pos, [1.0, 0.0, 0.0]);
+        // Flat normals are generated for primitives without them.
+        for vertex in &mesh.vertex_info {
+            assert_eq!(vertex.norm, [0.0, 0.0, 1.0]);
+        }
+    }
+
+    #[test]
+    fn triangle_strips_keep_their_winding_order() {
+        let mesh = load(r#"{ "primitives": [ { "attributes": { "POSITION": 1 }, "mode": 5 } ] }"#, r#"{ "mesh": 0 }"#).unwrap();
+        assert_eq!(mesh.indices.len(), 6);
+        for normal in face_normals(&mesh) {
+            assert_eq!(normal, Vector3::new(0.0, 0.0, 1.0));
+        }
+    }
+
+    #[test]
+    fn mirrored_nodes_flip_the_winding_order() {
+        let mesh = load(r#"{ "primitives": [ { "attributes": { "POSITION": 0 } } ] }"#,
+                        r#"{ "mesh": 0, "scale": [-1, 1, 1] }"#).unwrap();
+        assert_eq!(mesh.vertex_info[mesh.indices[0] as usize].pos[0], 0.0);
+        assert!(mesh.vertex_info.iter().any(|vertex| vertex.pos == [-1.0, 0.0, 0.0]));
+        // The triangle still faces the same way, so it is not culled.
+        assert_eq!(face_normals(&mesh), vec![Vector3::new(0.0, 0.0, 1.0)]);
+        for vertex in &mesh.vertex_info {
+            assert_eq!(vertex.norm, [0.0, 0.0, 1.0]);
+        }
+    }
+
+    #[test]
+    fn keeps_the_normals_of_primitives_that_have_them() {
+        let mesh = load(r#"{ "primitives": [
+                            { "attributes": { "POSITION": 0, "NORMAL": 2 } },
+                            { "attributes": { "POSITION": 0 } } ] }"#,
+                        r#"{ "mesh": 0 }"#).unwrap();
+        assert_eq!(mesh.submeshes.len(), 2);
+        for &i in &mesh.indices[..3] {
+            assert_eq!(mesh.vertex_info[i as usize].norm, [0.6, 0.0, 0.8]);
+        }
+        for &i in &mesh.indices[3..] {
+            assert_eq!(mesh.vertex_info[i as usize].norm, [0.0, 0.0, 1.0]);
+        }
+    }
+
+    #[test]
+    fn rejects_nodes_that_are_reached_twice() {
+        let triangle = r#"{ "primitives": [ { "attributes": { "POSITION": 0 } } ] }"#;
+        assert!(load(triangle, r#"{ "mesh": 0, "children": [1] }, { "mesh": 0 }"#).is_ok());
+        for nodes in &[r#"{ "children": [0] }"#,
+                       r#"{ "children": [1, 1] }, { "mesh": 0 }"#,
+                       r#"{ "children": [1, 2] }, { "children": [3] }, { "children": [3] }, { "mesh": 0 }"#] {
+            match load(triangle, nodes) {
+                Err(LoadError::Invalid(message)) => assert!(message.contains("more than once"), "{}", message),
+                other => panic!("expected an invalid node hierarchy, got {:?}", other.map(|_| ())),
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_accessors_with_bogus_counts() {
+        let gltf = document(r#"{ "primitives": [ { "attributes": { "POSITION": 3 } } ] }"#, r#"{ "mesh": 0 }"#);
+        // Without a buffer view, the count is all there is to go by.
+        let huge = gltf.replace(r#""type": "VEC3" }
+            ],"#, r#""type": "VEC3" },
+                { "componentType": 5126, "count": 1000000000000, "type": "VEC3" }
+            ],"#);
+        assert!(huge != gltf);
+        match GltfLoader::new().load_from_str(&huge) {
+            Err(LoadError::Invalid(message)) => assert!(message.contains("too many"), "{}", message),
+            other => panic!("expected an invalid accessor, got {:?}", other.map(|_| ())),
+        }
+
+        let too_long = gltf.replace(r#""type": "VEC3" }
+            ],"#, r#""type": "VEC3" },
+                { "bufferView": 0, "componentType": 5126, "count": 5, "type": "VEC3" }
+            ],"#);
+        assert!(GltfLoader::new().load_from_str(&too_long).is_err());
+    }
+
+    #[test]
+    fn decodes_base64_with_and_without_padding() {
+        assert_eq!(decode_base64("aGVsbG8=").unwrap(), b"hello");
+        assert_eq!(decode_base64("aGVsbG8").unwrap(), b"hello");
+        assert!(decode_base64("a?b").is_err());
+    }
+}
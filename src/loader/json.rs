@@ -0,0 +1,347 @@
+use std::collections::BTreeMap;
+use std::char;
+use std::str;
+
+use loader::error::LoadError;
+
+/// How deeply arrays and objects may be nested, so that malicious documents
+/// cannot overflow the stack.
+pub const MAX_DEPTH: usize = 128;
+
+/// A parsed JSON value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(BTreeMap<String, Json>),
+}
+
+impl Json {
+    /// The member ``key`` of an object; ``None`` for missing members and
+    /// values that are not objects.
+    pub fn get(&self, key: &str) -> Option<&Json> {
+        match *self {
+            Json::Object(ref members) => members.get(key),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            Json::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// The value as an index or count, if it is a non-negative integer.
+    pub fn as_usize(&self) -> Option<usize> {
+        match *self {
+            Json::Number(n) if n >= 0.0 && n.fract() == 0.0 => Some(n as usize),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Json::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            Json::String(ref s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[Json]> {
+        match *self {
+            Json::Array(ref values) => Some(values),
+            _ => None,
+        }
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    /// Number of arrays and objects the parser is inside of.
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// A parse error at byte ``start`` that shows the text from there up to
+    /// the current position.
+    fn error(&self, start: usize, message: &str) -> LoadError {
+        let before = &self.text[..start];
+        let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let mut end = if self.pos > start { self.pos } else { start };
+        while !self.text.is_char_boundary(end) {
+            end += 1;
+        }
+        let token = self.text[start..end].chars().take(20).collect::<String>();
+        LoadError::Parse {
+            line: before.matches('\n').count() + 1,
+            column: start - line_start + 1,
+            token,
+            message: message.to_string(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.bytes.len() {
+            match self.bytes[self.pos] {
+                b' ' | b'\t' | b'\n' | b'\r' => self.pos += 1,
+                _ => break,
+            }
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).cloned()
+    }
+
+    /// Skips ``expected`` if it comes next.
+    fn eat(&mut self, expected: u8) -> bool {
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Json, LoadError> {
+        self.skip_whitespace();
+        let start = self.pos;
+        match self.peek() {
+            Some(b'{') | Some(b'[') if self.depth >= MAX_DEPTH => {
+                self.pos += 1;
+                Err(self.error(start, "too deeply nested"))
+            },
+            Some(b'{') => {
+                self.depth += 1;
+                let object = self.parse_object();
+                self.depth -= 1;
+                object
+            },
+            Some(b'[') => {
+                self.depth += 1;
+                let array = self.parse_array();
+                self.depth -= 1;
+                array
+            },
+            Some(b'"') => Ok(Json::String(self.parse_string()?)),
+            Some(b'-') | Some(b'0'..=b'9') => self.parse_number(),
+            Some(_) => {
+                for &(word, ref value) in [("null", Json::Null), ("true", Json::Bool(true)), ("false", Json::Bool(false))].iter() {
+                    if self.text[start..].starts_with(word) {
+                        self.pos += word.len();
+                        return Ok(value.clone());
+                    }
+                }
+                self.pos += 1;
+                Err(self.error(start, "unexpected character"))
+            },
+            None => Err(self.error(start, "unexpected end of file")),
+        }
+    }
+
+    fn parse_object(&mut self) -> Result<Json, LoadError> {
+        self.pos += 1;
+        let mut members = BTreeMap::new();
+        self.skip_whitespace();
+        if self.eat(b'}') {
+            return Ok(Json::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some(b'"') {
+                return Err(self.error(self.pos, "expected a member name"));
+            }
+            let key = self.parse_string()?;
+            self.skip_whitespace();
+            if !self.eat(b':') {
+                return Err(self.error(self.pos, "expected ``:``"));
+            }
+            let value = self.parse_value()?;
+            members.insert(key, value);
+            self.skip_whitespace();
+            if self.eat(b'}') {
+                return Ok(Json::Object(members));
+            }
+            if !self.eat(b',') {
+                return Err(self.error(self.pos, "expected ``,`` or ``}``"));
+            }
+        }
+    }
+
+    fn parse_array(&mut self) -> Result<Json, LoadError> {
+        self.pos += 1;
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.eat(b']') {
+            return Ok(Json::Array(values));
+        }
+        loop {
+            values.push(self.parse_value()?);
+            self.skip_whitespace();
+            if self.eat(b']') {
+                return Ok(Json::Array(values));
+            }
+            if !self.eat(b',') {
+                return Err(self.error(self.pos, "expected ``,`` or ``]``"));
+            }
+        }
+    }
+
+    fn parse_hex4(&mut self) -> Result<u32, LoadError> {
+        let start = self.pos;
+        let digits = self.text.get(start..start + 4).unwrap_or("");
+        self.pos = (start + 4).min(self.bytes.len());
+        match u32::from_str_radix(digits, 16) {
+            Ok(code) if digits.len() == 4 => Ok(code),
+            _ => Err(self.error(start, "invalid unicode escape")),
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<String, LoadError> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut result = String::new();
+        loop {
+            // Copy everything up to the next quote or escape at once.
+            let run_start = self.pos;
+            while self.pos < self.bytes.len() && self.bytes[self.pos] != b'"' && self.bytes[self.pos] != b'\\' {
+                self.pos += 1;
+            }
+            result.push_str(&self.text[run_start..self.pos]);
+
+            match self.peek() {
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(result);
+                },
+                Some(_) => {
+                    let escape_start = self.pos;
+                    self.pos += 2;
+                    let c = match self.bytes.get(escape_start + 1) {
+                        Some(&b'"') => '"',
+                        Some(&b'\\') => '\\',
+                        Some(&b'/') => '/',
+                        Some(&b'b') => '\u{8}',
+                        Some(&b'f') => '\u{c}',
+                        Some(&b'n') => '\n',
+                        Some(&b'r') => '\r',
+                        Some(&b't') => '\t',
+                        Some(&b'u') => {
+                            let mut code = self.parse_hex4()?;
+                            // Characters outside the basic plane are written
+                            // as a surrogate pair.
+                            if (0xd800..0xdc00).contains(&code) && self.text[self.pos..].starts_with("\\u") {
+                                self.pos += 2;
+                                let low = self.parse_hex4()?;
+                                code = 0x10000 + ((code - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff);
+                            }
+                            char::from_u32(code).unwrap_or('\u{fffd}')
+                        },
+                        _ => {
+                            self.pos = self.pos.min(self.bytes.len());
+                            return Err(self.error(escape_start, "invalid escape"));
+                        },
+                    };
+                    result.push(c);
+                },
+                None => return Err(self.error(start, "unterminated string")),
+            }
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<Json, LoadError> {
+        let start = self.pos;
+        while self.pos < self.bytes.len() {
+            match self.bytes[self.pos] {
+                b'0'..=b'9' | b'-' | b'+' | b'.' | b'e' | b'E' => self.pos += 1,
+                _ => break,
+            }
+        }
+        match self.text[start..self.pos].parse::<f64>() {
+            Ok(n) => Ok(Json::Number(n)),
+            Err(_) => Err(self.error(start, "invalid number")),
+        }
+    }
+}
+
+/// Parses a JSON document. Errors point at the offending text.
+pub fn parse(text: &str) -> Result<Json, LoadError> {
+    let mut parser = Parser { text, bytes: text.as_bytes(), pos: 0, depth: 0 };
+    let value = parser.parse_value()?;
+    parser.skip_whitespace();
+    if parser.pos < text.len() {
+        let start = parser.pos;
+        parser.pos = text.len();
+        return Err(parser.error(start, "unexpected text after the end of the document"));
+    }
+    Ok(value)
+}
+
+/// Parses a JSON document from UTF-8 encoded ``bytes``.
+pub fn parse_bytes(bytes: &[u8]) -> Result<Json, LoadError> {
+    match str::from_utf8(bytes) {
+        Ok(text) => parse(text),
+        Err(e) => {
+            let valid = &bytes[..e.valid_up_to()];
+            let line_start = valid.iter().rposition(|&b| b == b'\n').map(|i| i + 1).unwrap_or(0);
+            Err(LoadError::Parse {
+                line: valid.iter().filter(|&&b| b == b'\n').count() + 1,
+                column: valid.len() - line_start + 1,
+                token: String::new(),
+                message: "invalid UTF-8".to_string(),
+            })
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use loader::error::LoadError;
+    use super::{parse, parse_bytes, Json, MAX_DEPTH};
+
+    #[test]
+    fn parses_nested_values() {
+        let json = parse(r#" { "a": [1, -2.5e1, true, null], "b": { "c": "\"q\" é 😀" } } "#).unwrap();
+        let a = json.get("a").and_then(|a| a.as_array()).unwrap();
+        assert_eq!(a, &[Json::Number(1.0), Json::Number(-25.0), Json::Bool(true), Json::Null][..]);
+        assert_eq!(a[0].as_usize(), Some(1));
+        assert_eq!(a[1].as_usize(), None);
+        assert_eq!(json.get("b").and_then(|b| b.get("c")).and_then(|c| c.as_str()), Some("\"q\" \u{e9} \u{1f600}"));
+    }
+
+    #[test]
+    fn reports_where_errors_are() {
+        match parse("{\n  \"a\": [1, 2,,]\n}") {
+            Err(LoadError::Parse { line, column, .. }) => assert_eq!((line, column), (2, 14)),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+        assert!(parse("[1] 2").is_err());
+        assert!(parse("\"unterminated").is_err());
+        assert!(parse_bytes(b"[\"\xff\"]").is_err());
+    }
+
+    #[test]
+    fn limits_the_nesting_depth() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+        assert!(parse(&nested(MAX_DEPTH)).is_ok());
+        match parse(&nested(MAX_DEPTH + 1)) {
+            Err(LoadError::Parse { message, .. }) => assert_eq!(message, "too deeply nested"),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+        // Deep enough to overflow the stack without the limit.
+        assert!(parse(&"[{\"a\":".repeat(1_000_000)).is_err());
+    }
+}
@@ -1,11 +1,24 @@
+pub mod error;
+pub mod json;
 pub mod obj_loader;
-
-use std::error::Error;
+pub mod vox_loader;
+pub mod triangulate;
+pub mod mtl_loader;
+pub mod gltf_loader;
 
 use model::mesh::Mesh;
+use loader::error::LoadError;
 
 pub trait MeshLoader {
-    fn load_from_str(&mut self, mesh_str: &str) -> Result<Mesh, Box<Error>>;
+    // The game picks the block out of the named meshes, so only the tests
+    // load a string as one mesh.
+    #[allow(dead_code)]
+    fn load_from_str(&mut self, mesh_str: &str) -> Result<Mesh, LoadError>;
+
+    fn load_from_file(&mut self, filename: &str) -> Result<Mesh, LoadError>;
+
+    /// Loads every named part of the source as a separate mesh.
+    fn load_named_from_str(&mut self, mesh_str: &str) -> Result<Vec<(String, Mesh)>, LoadError>;
 
-    fn load_from_file(&mut self, filename: &str) -> Result<Mesh, Box<Error>>;
+    fn load_named_from_file(&mut self, filename: &str) -> Result<Vec<(String, Mesh)>, LoadError>;
 }
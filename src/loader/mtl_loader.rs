@@ -0,0 +1,158 @@
+use std::io::{BufReader, Read};
+use std::fs::File;
+use std::path::Path;
+use std::str::FromStr;
+
+use util::types::Float;
+use model::material::Material;
+use loader::error::{self, LoadError};
+
+/// Reads Wavefront ``.mtl`` material libraries. Only the diffuse color and
+/// texture are used; other properties are skipped.
+pub struct MtlLoader {
+    materials: Vec<Material>,
+    lenient: bool,
+    /// Number of the line that is being parsed, for error messages.
+    line_number: usize,
+}
+
+/// Properties that are valid but not used.
+const IGNORED_KEYS: &[&str] = &[
+    "Ka", "Ks", "Ke", "Ns", "Ni", "d", "Tr", "Tf", "illum", "sharpness",
+    "map_Ka", "map_Ks", "map_Ns", "map_d", "map_bump", "bump", "disp", "decal", "refl",
+];
+
+impl MtlLoader {
+    pub fn new() -> MtlLoader {
+        MtlLoader { materials: Vec::new(), lenient: false, line_number: 0 }
+    }
+
+    pub fn set_lenient(&mut self, lenient: bool) {
+        self.lenient = lenient;
+    }
+
+    fn parse_error(&self, line: &str, token: Option<&str>, message: &str) -> LoadError {
+        LoadError::Parse {
+            line: self.line_number,
+            column: token.map(|token| error::column(line, token)).unwrap_or(line.len() + 1),
+            token: token.unwrap_or("").to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    fn parse_token<T>(&self, line: &str, token: Option<&str>, what: &str) -> Result<T, LoadError>
+        where T: FromStr
+    {
+        match token {
+            Some(t) => t.parse::<T>().map_err(|_| self.parse_error(line, token, &format!("invalid {}", what))),
+            None => Err(self.parse_error(line, None, &format!("missing {}", what))),
+        }
+    }
+
+    fn handle_line(&mut self, line: &str, base_dir: &Path) -> Result<(), LoadError> {
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("newmtl") => {
+                let name = words.next().ok_or_else(|| self.parse_error(line, None, "missing material name"))?;
+                self.materials.push(Material::new(name));
+                Ok(())
+            },
+            Some(key @ "Kd") => {
+                let r = self.parse_token::<Float>(line, words.next(), "color component")?;
+                let g = self.parse_token::<Float>(line, words.next(), "color component")?;
+                let b = self.parse_token::<Float>(line, words.next(), "color component")?;
+                self.current_material(line, key)?.diffuse_color = [r, g, b];
+                Ok(())
+            },
+            Some(key @ "map_Kd") => {
+                // Texture options come before the file name, which is last.
+                let filename = words.last().ok_or_else(|| self.parse_error(line, None, "missing texture file name"))?;
+                self.current_material(line, key)?.diffuse_texture = Some(base_dir.join(filename));
+                Ok(())
+            },
+            Some(key) if IGNORED_KEYS.contains(&key) || key.starts_with('#') || self.lenient => Ok(()),
+            Some(directive) => Err(LoadError::UnsupportedDirective { line: self.line_number, directive: directive.to_string() }),
+            None => Ok(()),
+        }
+    }
+
+    fn current_material(&mut self, line: &str, key: &str) -> Result<&mut Material, LoadError> {
+        if self.materials.is_empty() {
+            return Err(self.parse_error(line, Some(key), "property before the first material"));
+        }
+        Ok(self.materials.last_mut().unwrap())
+    }
+
+    /// Reads the materials from ``read_from``. Texture paths are relative to
+    /// ``base_dir``.
+    pub fn load_materials<T>(&mut self, read_from: T, base_dir: &Path) -> Result<Vec<Material>, LoadError>
+        where T: Read
+    {
+        let lines = error::read_lines(BufReader::new(read_from))?;
+        for (i, line) in lines.iter().enumerate() {
+            self.line_number = i + 1;
+            if let Err(e) = self.handle_line(line, base_dir) {
+                self.materials.clear();
+                return Err(e);
+            }
+        }
+        Ok(self.materials.drain(..).collect())
+    }
+
+    pub fn load_from_file(&mut self, path: &Path) -> Result<Vec<Material>, LoadError> {
+        let f = File::open(path)?;
+        let base_dir = path.parent().unwrap_or(Path::new(""));
+        self.load_materials(f, base_dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::path::Path;
+
+    use model::material::Material;
+    use loader::error::LoadError;
+    use super::MtlLoader;
+
+    fn load(mtl: &str, lenient: bool) -> Result<Vec<Material>, LoadError> {
+        let mut loader = MtlLoader::new();
+        loader.set_lenient(lenient);
+        loader.load_materials(Cursor::new(mtl), Path::new("models"))
+    }
+
+    #[test]
+    fn reads_diffuse_colors_and_textures() {
+        let materials = load("# two materials\n\
+                              newmtl red\n\
+                              Ka 0 0 0\n\
+                              Kd 1 0 0\n\
+                              illum 2\n\
+                              newmtl brick\n\
+                              map_Kd -s 2 2 1 textures/brick.png\n", false).unwrap();
+        assert_eq!(materials.len(), 2);
+        assert_eq!(materials[0].name, "red");
+        assert_eq!(materials[0].diffuse_color, [1.0, 0.0, 0.0]);
+        assert_eq!(materials[0].diffuse_texture, None);
+        assert_eq!(materials[1].diffuse_color, [1.0; 3]);
+        assert_eq!(materials[1].diffuse_texture, Some(Path::new("models").join("textures/brick.png")));
+    }
+
+    #[test]
+    fn rejects_properties_before_the_first_material() {
+        match load("Kd 1 1 1\n", false) {
+            Err(LoadError::Parse { line: 1, column: 1, .. }) => (),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn skips_unsupported_properties_only_when_lenient() {
+        let mtl = "newmtl glass\nPr 0.5\n";
+        match load(mtl, false) {
+            Err(LoadError::UnsupportedDirective { line: 2, ref directive }) if directive == "Pr" => (),
+            other => panic!("expected an unsupported directive, got {:?}", other),
+        }
+        assert_eq!(load(mtl, true).unwrap().len(), 1);
+    }
+}
@@ -1,60 +1,143 @@
 use std::io::{Cursor, BufReader, Read};
-use std::io::prelude::*;
 use std::fs::File;
-use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::collections::HashMap;
 
 use util::types::{Float, GLIndex};
-use model::mesh::Mesh;
+use model::mesh::{Mesh, NormalMode};
+use model::material::Material;
 use loader::MeshLoader;
+use loader::error::{self, LoadError};
+use loader::triangulate::triangulate;
+use loader::mtl_loader::MtlLoader;
 use gl::vertex::Vertex;
 
-pub struct ObjLoader {
+/// Faces that meet at a smaller angle than this keep a sharp edge when
+/// normals are generated.
+const CREASE_ANGLE: Float = ::std::f32::consts::FRAC_PI_6;
+
+/// Name of the mesh that holds the faces before the first object or group.
+const DEFAULT_OBJECT_NAME: &str = "default";
+
+/// A mesh that is built from an object or group of an .obj file. Every
+/// object has its own vertices, so ``index_map`` maps the indices of a face
+/// vertex to the index of the vertex in this mesh.
+struct ObjectMesh {
+    name: String,
+    mesh: Mesh,
     index_map: HashMap<[Option<GLIndex>; 3], GLIndex>,
+}
+
+/// Loads Wavefront .obj files.
+///
+/// In lenient mode, directives that are not supported are skipped instead
+/// of failing the whole file. Face vertices without a normal get one
+/// smooth normals generated; normals from the file are kept.
+pub struct ObjLoader {
     verts: Vec<[Float; 3]>,
     vert_normals: Vec<[Float; 3]>,
     tex_coords: Vec<[Float; 2]>,
+    materials: Vec<Material>,
+    /// Directory that material libraries are looked up in.
+    base_dir: PathBuf,
+    objects: Vec<ObjectMesh>,
+    current_object: usize,
+    current_material: Option<usize>,
+    /// Whether objects and groups are loaded as separate meshes.
+    split_objects: bool,
+    lenient: bool,
+    /// Number of the line that is being parsed, for error messages.
+    line_number: usize,
 }
 
 impl ObjLoader {
     pub fn new() -> ObjLoader {
         ObjLoader { 
-            index_map: HashMap::new(), 
             verts: Vec::new(), 
             vert_normals: Vec::new(),
             tex_coords: Vec::new(),
+            materials: Vec::new(),
+            base_dir: PathBuf::new(),
+            objects: Vec::new(),
+            current_object: 0,
+            current_material: None,
+            split_objects: false,
+            lenient: false,
+            line_number: 0,
+        }
+    }
+
+    pub fn set_lenient(&mut self, lenient: bool) {
+        self.lenient = lenient;
+    }
+
+    /// A parse error at ``token`` of ``line``, or at the end of the line if
+    /// the token is missing.
+    fn parse_error(&self, line: &str, token: Option<&str>, message: &str) -> LoadError {
+        LoadError::Parse {
+            line: self.line_number,
+            column: token.map(|token| error::column(line, token)).unwrap_or(line.len() + 1),
+            token: token.unwrap_or("").to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    fn parse_token<T>(&self, line: &str, token: Option<&str>, what: &str) -> Result<T, LoadError>
+        where T: FromStr
+    {
+        match token {
+            Some(t) => t.parse::<T>().map_err(|_| self.parse_error(line, token, &format!("invalid {}", what))),
+            None => Err(self.parse_error(line, None, &format!("missing {}", what))),
         }
     }
 
-    fn handle_line(&mut self, line: &str, mesh: &mut Mesh) -> Result<(), Box<Error>> {
+    /// Makes the object called ``name`` the one that faces are added to,
+    /// creating it if it does not exist yet.
+    fn select_object(&mut self, name: &str) {
+        self.current_object = match self.objects.iter().position(|object| object.name == name) {
+            Some(index) => index,
+            None => {
+                self.objects.push(ObjectMesh { name: name.to_string(), mesh: Mesh::new(), index_map: HashMap::new() });
+                self.objects.len() - 1
+            },
+        };
+        self.objects[self.current_object].mesh.begin_submesh(self.current_material);
+    }
+
+    fn handle_line(&mut self, line: &str) -> Result<(), LoadError> {
         let mut words = line.split_whitespace();
         match words.next() {
             Some(vertex_type @ "v") | Some(vertex_type @ "vn") => {
-                if let (Some(a), Some(b), Some(c)) = (words.next(), words.next(), words.next()) {
-                    let (x, y, z) = (a.parse()?, b.parse()?, c.parse()?);
-                    Ok(match vertex_type {
-                        "v" => self.verts.push([x, y, z]),
-                        "vn" => self.vert_normals.push([x, y, z]),
-                        _ => (),
-                    })
-                } else {
-                    Err(Box::<Error>::from(format!("Invalid vertex: ``{}``", line)))
-                }
+                let x = self.parse_token(line, words.next(), "coordinate")?;
+                let y = self.parse_token(line, words.next(), "coordinate")?;
+                let z = self.parse_token(line, words.next(), "coordinate")?;
+                match vertex_type {
+                    "v" => self.verts.push([x, y, z]),
+                    "vn" => self.vert_normals.push([x, y, z]),
+                    _ => (),
+                };
+                Ok(())
             },
             Some("vt") => {
-                if let(Some(a), Some(b)) = (words.next(), words.next()) {
-                    let (x, y) = (a.parse()?, b.parse()?);
-                    Ok(self.tex_coords.push([x, y]))
-                } else {
-                    Err(Box::<Error>::from(format!("Only 2d texture coordinates are supported: ``{}``", line)))
-                }
+                let u = self.parse_token(line, words.next(), "texture coordinate")?;
+                let v = self.parse_token(line, words.next(), "texture coordinate")?;
+                self.tex_coords.push([u, v]);
+                Ok(())
             },
             Some("f") => {
-                for indices_str in words {
-                    let parsed_indices = self.parse_face_vertex(indices_str)?;
-                    if self.index_map.contains_key(&parsed_indices) {
-                        let cached_vertex_pos = *self.index_map.get(&parsed_indices).unwrap();
-                        mesh.indices.push(cached_vertex_pos);
+                let parsed = words.map(|indices_str| self.parse_face_vertex(line, indices_str))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if parsed.len() < 3 {
+                    return Err(self.parse_error(line, None, "a face needs at least 3 vertices"));
+                }
+
+                let object = &mut self.objects[self.current_object];
+                let mesh = &mut object.mesh;
+                let mut face = Vec::new();
+                for parsed_indices in parsed {
+                    if let Some(&cached_vertex_pos) = object.index_map.get(&parsed_indices) {
+                        face.push(cached_vertex_pos);
                     } else if let [Some(index_v), index_vt, index_vn] = parsed_indices {
                         let vertex_info_len = mesh.vertex_info.len() as GLIndex;
                         let vn = match index_vn {
@@ -66,23 +149,88 @@ impl ObjLoader {
                             None => [0.0; 2],
                         };
                         mesh.vertex_info.push(Vertex::new(self.verts[index_v as usize], vt, vn));
-                        mesh.indices.push(vertex_info_len);
-                        self.index_map.insert(parsed_indices, vertex_info_len);
+                        face.push(vertex_info_len);
+                        object.index_map.insert(parsed_indices, vertex_info_len);
                     }
                 }
+
+                let polygon = face.iter().map(|&i| mesh.vertex_info[i as usize].pos).collect::<Vec<_>>();
+                for triangle in triangulate(&polygon) {
+                    mesh.indices.extend(triangle.iter().map(|&i| face[i]));
+                }
                 Ok(())
             }
-            //Ignore object markers, object groups, materials, and smoothing groups for now
-            Some("o") | Some("s") | Some("g") | Some("#") | Some("mtllib") | Some("usemtl") => Ok(()),
-            Some(_) | None => Err(Box::<Error>::from(format!("Unrecognized line in .obj file: ``{}``", line))),
+            Some("mtllib") => {
+                for filename in words {
+                    let path = self.base_dir.join(filename);
+                    let mut mtl_loader = MtlLoader::new();
+                    mtl_loader.set_lenient(self.lenient);
+                    let materials = mtl_loader.load_from_file(&path)
+                        .map_err(|e| LoadError::Referenced { path: path.clone(), error: Box::new(e) })?;
+                    self.materials.extend(materials);
+                }
+                Ok(())
+            },
+            Some("usemtl") => {
+                let name = words.next().ok_or_else(|| self.parse_error(line, None, "missing material name"))?;
+                // Materials that are not defined in a library are added with
+                // default properties.
+                let index = match self.materials.iter().position(|material| material.name == name) {
+                    Some(index) => index,
+                    None => {
+                        self.materials.push(Material::new(name));
+                        self.materials.len() - 1
+                    },
+                };
+                self.current_material = Some(index);
+                self.objects[self.current_object].mesh.begin_submesh(Some(index));
+                Ok(())
+            },
+            Some("o") | Some("g") => {
+                if self.split_objects {
+                    let name = words.collect::<Vec<&str>>().join(" ");
+                    self.select_object(if name.is_empty() { DEFAULT_OBJECT_NAME } else { &name });
+                }
+                Ok(())
+            },
+            //Ignore smoothing groups for now
+            Some("s") => Ok(()),
+            Some(comment) if comment.starts_with('#') => Ok(()),
+            Some(_) if self.lenient => Ok(()),
+            Some(directive) => Err(LoadError::UnsupportedDirective { line: self.line_number, directive: directive.to_string() }),
+            None => Ok(()),
+        }
+    }
+
+    /// Turns a one-based index, or a negative index relative to the end of a
+    /// list of ``len`` elements, into a zero-based index. An empty string
+    /// means that the index is absent.
+    fn resolve_index(&self, line: &str, index_str: &str, len: usize) -> Result<Option<GLIndex>, LoadError> {
+        if index_str.is_empty() {
+            return Ok(None);
+        }
+
+        let index = self.parse_token::<i64>(line, Some(index_str), "index")?;
+        let resolved = if index < 0 { len as i64 + index } else { index - 1 };
+        if index == 0 || resolved < 0 || resolved >= len as i64 {
+            return Err(LoadError::IndexOutOfRange { line: self.line_number, column: error::column(line, index_str), index });
         }
+        Ok(Some(resolved as GLIndex))
     }
 
-    fn parse_face_vertex(&self, face_str: &str) -> Result<[Option<GLIndex>; 3], Box<Error>> {
-        let indices = face_str.split('/').take(3).collect::<Vec<&str>>();
-        let index_v = indices[0].parse::<GLIndex>()? - 1;
-        let index_vt = indices[1].parse::<GLIndex>().map(|i| { Some(i - 1) }).unwrap_or(None);
-        let index_vn = indices[2].parse::<GLIndex>().map(|i| { Some(i - 1) }).unwrap_or(None);
+    /// Parses a face vertex in one of the forms ``v``, ``v/vt``, ``v//vn`` and
+    /// ``v/vt/vn`` into zero-based indices.
+    fn parse_face_vertex(&self, line: &str, face_str: &str) -> Result<[Option<GLIndex>; 3], LoadError> {
+        let indices = face_str.split('/').collect::<Vec<&str>>();
+        if indices.len() > 3 {
+            return Err(self.parse_error(line, Some(face_str), "invalid face vertex"));
+        }
+
+        let index_str = |i: usize| indices.get(i).cloned().unwrap_or("");
+        let index_v = self.resolve_index(line, index_str(0), self.verts.len())?
+            .ok_or_else(|| self.parse_error(line, Some(face_str), "face vertex without a position"))?;
+        let index_vt = self.resolve_index(line, index_str(1), self.tex_coords.len())?;
+        let index_vn = self.resolve_index(line, index_str(2), self.vert_normals.len())?;
         Ok([Some(index_v), index_vt, index_vn])
     }
 
@@ -90,35 +238,256 @@ impl ObjLoader {
         self.verts.clear();
         self.vert_normals.clear();
         self.tex_coords.clear();
-        self.index_map.clear();
+        self.materials.clear();
+        self.objects.clear();
+        self.current_object = 0;
+        self.current_material = None;
+        self.line_number = 0;
     }
 
-    fn load_mesh<T>(&mut self, read_from: T) -> Result<Mesh, Box<Error>> 
+    /// Loads the faces of every object and group into a separate mesh if
+    /// ``split_objects`` is set, or all faces into one mesh otherwise. Objects
+    /// without faces are left out when splitting.
+    fn load_objects<T>(&mut self, read_from: T, split_objects: bool) -> Result<Vec<(String, Mesh)>, LoadError> 
         where T: Read
     {
-        let reader = BufReader::new(read_from);
-        let mut mesh = Mesh::new();
-        for line in reader.lines().map(|line| line.unwrap()) {
-            let trimmed = line.trim();
-            if !trimmed.is_empty() {
-                match self.handle_line(trimmed, &mut mesh) {
-                    Ok(_) => (),
-                    Err(e) => return Err(Box::<Error>::from(format!("Failed to parse .obj file: {}", e)))
-                }
+        let lines = error::read_lines(BufReader::new(read_from))?;
+        self.split_objects = split_objects;
+        self.select_object(DEFAULT_OBJECT_NAME);
+        for (i, line) in lines.iter().enumerate() {
+            self.line_number = i + 1;
+            if let Err(e) = self.handle_line(line) {
+                self.clear();
+                return Err(e);
+            }
+        }
+
+        let mut meshes = Vec::new();
+        for mut object in self.objects.drain(..) {
+            object.mesh.end_submesh();
+            object.mesh.materials = self.materials.clone();
+            if object.mesh.has_missing_normals() {
+                object.mesh.compute_missing_normals(NormalMode::Smooth { crease_angle: CREASE_ANGLE });
+            }
+            if !split_objects || !object.mesh.indices.is_empty() {
+                meshes.push((object.name, object.mesh));
             }
         }
         self.clear();
-        Ok(mesh)
+        Ok(meshes)
+    }
+
+    fn load_mesh<T>(&mut self, read_from: T) -> Result<Mesh, LoadError> 
+        where T: Read
+    {
+        let mut meshes = self.load_objects(read_from, false)?;
+        Ok(meshes.pop().unwrap().1)
     }
 }
 
 impl MeshLoader for ObjLoader {
-    fn load_from_str(&mut self, mesh_str: &str) -> Result<Mesh, Box<Error>> {
+    /// Material libraries are looked up relative to the working directory.
+    fn load_from_str(&mut self, mesh_str: &str) -> Result<Mesh, LoadError> {
+        self.base_dir = PathBuf::new();
         self.load_mesh(Cursor::new(mesh_str))
     }
 
-    fn load_from_file(&mut self, filename: &str) -> Result<Mesh, Box<Error>> {
+    /// Material libraries are looked up next to the file.
+    fn load_from_file(&mut self, filename: &str) -> Result<Mesh, LoadError> {
         let f = File::open(filename)?;
+        self.base_dir = Path::new(filename).parent().map(|dir| dir.to_path_buf()).unwrap_or_default();
         self.load_mesh(f)
     }
+
+    /// Every object (``o``) and group (``g``) becomes a mesh with its name.
+    /// Faces that come before the first of them are put in a mesh called
+    /// ``default``.
+    fn load_named_from_str(&mut self, mesh_str: &str) -> Result<Vec<(String, Mesh)>, LoadError> {
+        self.base_dir = PathBuf::new();
+        self.load_objects(Cursor::new(mesh_str), true)
+    }
+
+    fn load_named_from_file(&mut self, filename: &str) -> Result<Vec<(String, Mesh)>, LoadError> {
+        let f = File::open(filename)?;
+        self.base_dir = Path::new(filename).parent().map(|dir| dir.to_path_buf()).unwrap_or_default();
+        self.load_objects(f, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+    use std::io::Write;
+
+    use util::types::Float;
+    use loader::MeshLoader;
+    use loader::error::LoadError;
+    use super::ObjLoader;
+
+    const SQUARE: &str = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n";
+
+    #[test]
+    fn keeps_normals_from_the_file_when_others_are_missing() {
+        let obj = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\n\
+                   vn 0.6 0 0.8\n\
+                   f 1//1 2//1 3//1\n\
+                   f 1 3 4\n";
+        let mesh = ObjLoader::new().load_from_str(obj).unwrap();
+        assert!(!mesh.has_missing_normals());
+        for &i in &mesh.indices[..3] {
+            assert_eq!(mesh.vertex_info[i as usize].norm, [0.6, 0.0, 0.8]);
+        }
+        for &i in &mesh.indices[3..] {
+            assert_eq!(mesh.vertex_info[i as usize].norm, [0.0, 0.0, 1.0]);
+        }
+    }
+
+    #[test]
+    fn triangulates_polygons() {
+        let mesh = ObjLoader::new().load_from_str(&format!("{}f 1 2 3 4\n", SQUARE)).unwrap();
+        assert_eq!(mesh.vertex_info.len(), 4);
+        assert_eq!(mesh.indices.len(), 6);
+    }
+
+    #[test]
+    fn resolves_negative_indices_relative_to_the_end() {
+        let obj = format!("{}vt 0.5 0.5\nvn 0 0 1\nf -4/-1/-1 -3/1/1 -2/-1/1\n", SQUARE);
+        let mesh = ObjLoader::new().load_from_str(&obj).unwrap();
+        let positions = mesh.indices.iter().map(|&i| mesh.vertex_info[i as usize].pos).collect::<Vec<_>>();
+        assert_eq!(positions, vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0]]);
+        assert!(mesh.vertex_info.iter().all(|vertex| vertex.tex_coords == [0.5, 0.5]));
+        // The same indices written differently share one vertex.
+        assert_eq!(mesh.vertex_info.len(), 3);
+    }
+
+    #[test]
+    fn reads_every_face_vertex_form() {
+        let obj = format!("{}vt 0.25 0.75\nvn 1 0 0\nf 1/1 2//1 3/1/1 4\n", SQUARE);
+        let mesh = ObjLoader::new().load_from_str(&obj).unwrap();
+        assert_eq!(mesh.indices.len(), 6);
+        let vertex = |pos: [Float; 3]| *mesh.vertex_info.iter().find(|vertex| vertex.pos == pos).unwrap();
+        assert_eq!(vertex([0.0, 0.0, 0.0]).tex_coords, [0.25, 0.75]);
+        assert_eq!(vertex([1.0, 0.0, 0.0]).norm, [1.0, 0.0, 0.0]);
+        assert_eq!(vertex([1.0, 0.0, 0.0]).tex_coords, [0.0, 0.0]);
+        assert_eq!(vertex([1.0, 1.0, 0.0]).tex_coords, [0.25, 0.75]);
+        assert_eq!(vertex([1.0, 1.0, 0.0]).norm, [1.0, 0.0, 0.0]);
+        assert_eq!(vertex([0.0, 1.0, 0.0]).tex_coords, [0.0, 0.0]);
+    }
+
+    #[test]
+    fn rejects_indices_out_of_range() {
+        for face in &["f 1 2 5", "f 1 2 -5", "f 0 1 2", "f 1/1 2 3", "f 1//1 2 3"] {
+            match ObjLoader::new().load_from_str(&format!("{}{}\n", SQUARE, face)) {
+                Err(LoadError::IndexOutOfRange { line: 5, .. }) => (),
+                other => panic!("``{}`` gave {:?}", face, other.map(|_| ())),
+            }
+        }
+        assert!(ObjLoader::new().load_from_str(&format!("{}f 1/2/3/4 2 3\n", SQUARE)).is_err());
+        assert!(ObjLoader::new().load_from_str(&format!("{}f 1 2\n", SQUARE)).is_err());
+    }
+
+    #[test]
+    fn splits_faces_into_submeshes_per_material() {
+        let obj = format!("{}f 1 2 3\n\
+                           usemtl red\n\
+                           f 1 2 3\n\
+                           f 1 3 4\n\
+                           usemtl blue\n\
+                           usemtl red\n\
+                           f 1 2 3 4\n", SQUARE);
+        let mesh = ObjLoader::new().load_from_str(&obj).unwrap();
+        let names = mesh.materials.iter().map(|material| material.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["red", "blue"]);
+        // The faces before the first ``usemtl`` have no material, and the
+        // empty range of ``blue`` is dropped.
+        let ranges = mesh.submeshes.iter().map(|s| (s.material, s.start, s.count)).collect::<Vec<_>>();
+        assert_eq!(ranges, vec![(None, 0, 3), (Some(0), 3, 6), (Some(0), 9, 6)]);
+        assert_eq!(mesh.draw_ranges().len(), 3);
+    }
+
+    #[test]
+    fn reads_material_libraries_next_to_the_file() {
+        let dir = env::temp_dir().join(format!("vak-obj-test-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::File::create(dir.join("model.mtl")).unwrap().write_all(b"newmtl green\nKd 0 1 0\nmap_Kd grass.png\n").unwrap();
+        fs::File::create(dir.join("model.obj")).unwrap()
+            .write_all(format!("mtllib model.mtl\n{}usemtl green\nf 1 2 3\n", SQUARE).as_bytes()).unwrap();
+        let mesh = ObjLoader::new().load_from_file(dir.join("model.obj").to_str().unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+
+        let mesh = mesh.unwrap();
+        assert_eq!(mesh.materials.len(), 1);
+        assert_eq!(mesh.materials[0].diffuse_color, [0.0, 1.0, 0.0]);
+        assert_eq!(mesh.materials[0].diffuse_texture, Some(dir.join("grass.png")));
+        assert_eq!(mesh.submeshes[0].material, Some(0));
+    }
+
+    #[test]
+    fn reports_missing_material_libraries() {
+        match ObjLoader::new().load_from_str("mtllib does-not-exist.mtl\n") {
+            Err(LoadError::Referenced { .. }) => (),
+            other => panic!("expected an error in the library, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn loads_objects_and_groups_as_named_meshes() {
+        let obj = format!("{}f 1 2 3\n\
+                           o Slab\n\
+                           f 1 2 3\n\
+                           g Torch  Flame\n\
+                           o Empty\n\
+                           g Torch  Flame\n\
+                           f 1 2 3 4\n\
+                           o Slab\n\
+                           f 1 3 4\n", SQUARE);
+        let meshes = ObjLoader::new().load_named_from_str(&obj).unwrap();
+        let summary = meshes.iter().map(|(name, mesh)| (name.as_str(), mesh.indices.len())).collect::<Vec<_>>();
+        // Objects without faces are left out, and faces of an object that
+        // comes again are added to it.
+        assert_eq!(summary, vec![("default", 3), ("Slab", 6), ("Torch Flame", 6)]);
+
+        let merged = ObjLoader::new().load_from_str(&obj).unwrap();
+        assert_eq!(merged.indices.len(), 15);
+    }
+
+    #[test]
+    fn reports_the_line_and_column_of_bad_tokens() {
+        let obj = format!("{}vt 0.5\nv 1 two 3\n", SQUARE);
+        match ObjLoader::new().load_from_str(&obj) {
+            Err(LoadError::Parse { line: 5, column: 7, ref token, .. }) if token.is_empty() => (),
+            other => panic!("expected a missing coordinate, got {:?}", other.map(|_| ())),
+        }
+        match ObjLoader::new().load_from_str(&format!("{}v 1 two 3\n", SQUARE)) {
+            Err(LoadError::Parse { line: 5, column: 5, ref token, .. }) if token == "two" => (),
+            other => panic!("expected an invalid coordinate, got {:?}", other.map(|_| ())),
+        }
+        match ObjLoader::new().load_from_str(&format!("{}f 1 2 -9\n", SQUARE)) {
+            Err(LoadError::IndexOutOfRange { line: 5, column: 7, index: -9 }) => (),
+            other => panic!("expected an index out of range, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn skips_unsupported_directives_only_when_lenient() {
+        let obj = format!("{}curv 0 1 1 2\nf 1 2 3\n", SQUARE);
+        match ObjLoader::new().load_from_str(&obj) {
+            Err(LoadError::UnsupportedDirective { line: 5, ref directive }) if directive == "curv" => (),
+            other => panic!("expected an unsupported directive, got {:?}", other.map(|_| ())),
+        }
+
+        let mut loader = ObjLoader::new();
+        loader.set_lenient(true);
+        assert_eq!(loader.load_from_str(&obj).unwrap().indices.len(), 3);
+        // Lenient mode does not hide malformed lines of known directives.
+        assert!(loader.load_from_str(&format!("{}v 1 2\n", SQUARE)).is_err());
+    }
+
+    #[test]
+    fn can_load_again_after_an_error() {
+        let mut loader = ObjLoader::new();
+        assert!(loader.load_from_str("v 1 2 3\nnonsense\n").is_err());
+        let mesh = loader.load_from_str(&format!("{}f 1 2 3\n", SQUARE)).unwrap();
+        assert_eq!(mesh.vertex_info.len(), 3);
+    }
 }
@@ -0,0 +1,143 @@
+use cgmath::{Vector3, InnerSpace};
+
+use util::types::Float;
+
+/// Normal of a polygon by Newell's method, which also works for concave and
+/// slightly non-planar polygons. Its length is twice the polygon's area.
+fn polygon_normal(polygon: &[[Float; 3]]) -> Vector3<Float> {
+    let mut normal = Vector3::new(0.0, 0.0, 0.0);
+    for (i, a) in polygon.iter().enumerate() {
+        let b = polygon[(i + 1) % polygon.len()];
+        normal.x += (a[1] - b[1]) * (a[2] + b[2]);
+        normal.y += (a[2] - b[2]) * (a[0] + b[0]);
+        normal.z += (a[0] - b[0]) * (a[1] + b[1]);
+    }
+    normal
+}
+
+/// Twice the signed area of the 2d triangle ``abc``; positive if it is
+/// counter-clockwise.
+fn cross_2d(a: [Float; 2], b: [Float; 2], c: [Float; 2]) -> Float {
+    (b[0] - a[0]) * (c[1] - a[1]) - (b[1] - a[1]) * (c[0] - a[0])
+}
+
+fn in_triangle(p: [Float; 2], a: [Float; 2], b: [Float; 2], c: [Float; 2]) -> bool {
+    cross_2d(a, b, p) >= 0.0 && cross_2d(b, c, p) >= 0.0 && cross_2d(c, a, p) >= 0.0
+}
+
+/// Splits a polygon into triangles that keep its winding order. The result
+/// holds indices into ``polygon``.
+///
+/// Concave polygons are triangulated by ear clipping in the plane the
+/// polygon is most aligned with. Degenerate polygons, for which no ear can
+/// be found, fall back to a triangle fan.
+pub fn triangulate(polygon: &[[Float; 3]]) -> Vec<[usize; 3]> {
+    let n = polygon.len();
+    if n < 3 {
+        return Vec::new();
+    }
+    if n == 3 {
+        return vec![[0, 1, 2]];
+    }
+
+    // Project onto the plane orthogonal to the largest component of the
+    // normal, keeping the polygon counter-clockwise in 2d.
+    let normal = polygon_normal(polygon);
+    let abs = [normal.x.abs(), normal.y.abs(), normal.z.abs()];
+    let axis = if abs[0] >= abs[1] && abs[0] >= abs[2] { 0 } else if abs[1] >= abs[2] { 1 } else { 2 };
+    let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+    let flip = normal[axis] < 0.0;
+    let points = polygon.iter()
+        .map(|p| if flip { [p[v], p[u]] } else { [p[u], p[v]] })
+        .collect::<Vec<_>>();
+
+    if normal.magnitude2() == 0.0 {
+        return (1..n - 1).map(|i| [0, i, i + 1]).collect();
+    }
+
+    let mut remaining = (0..n).collect::<Vec<usize>>();
+    let mut triangles = Vec::with_capacity(n - 2);
+    while remaining.len() > 3 {
+        let len = remaining.len();
+        let ear = (0..len).find(|&i| {
+            let (a, b, c) = (remaining[(i + len - 1) % len], remaining[i], remaining[(i + 1) % len]);
+            cross_2d(points[a], points[b], points[c]) > 0.0 && remaining.iter()
+                .filter(|&&p| p != a && p != b && p != c)
+                .all(|&p| !in_triangle(points[p], points[a], points[b], points[c]))
+        });
+
+        match ear {
+            Some(i) => {
+                triangles.push([remaining[(i + len - 1) % len], remaining[i], remaining[(i + 1) % len]]);
+                remaining.remove(i);
+            },
+            None => break,
+        }
+    }
+
+    for i in 1..remaining.len() - 1 {
+        triangles.push([remaining[0], remaining[i], remaining[i + 1]]);
+    }
+    triangles
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Vector3, InnerSpace};
+
+    use util::types::Float;
+    use super::triangulate;
+
+    /// Twice the area of every triangle, signed by whether it faces
+    /// ``normal``.
+    fn signed_areas(polygon: &[[Float; 3]], triangles: &[[usize; 3]], normal: Vector3<Float>) -> Vec<Float> {
+        triangles.iter().map(|t| {
+            let p = |i: usize| Vector3::new(polygon[t[i]][0], polygon[t[i]][1], polygon[t[i]][2]);
+            (p(1) - p(0)).cross(p(2) - p(0)).dot(normal)
+        }).collect()
+    }
+
+    /// An L shape with a reflex corner at (1, 1), counter-clockwise when seen
+    /// from +Z.
+    fn l_shape() -> Vec<[Float; 3]> {
+        vec![[0.0, 0.0, 0.0], [2.0, 0.0, 0.0], [2.0, 1.0, 0.0], [1.0, 1.0, 0.0], [1.0, 2.0, 0.0], [0.0, 2.0, 0.0]]
+    }
+
+    #[test]
+    fn triangles_and_quads_are_fans() {
+        assert_eq!(triangulate(&l_shape()[..3]), vec![[0, 1, 2]]);
+        let square = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]];
+        assert_eq!(triangulate(&square).len(), 2);
+        assert!(triangulate(&square[..2]).is_empty());
+    }
+
+    #[test]
+    fn concave_polygons_keep_their_area_and_winding() {
+        let polygon = l_shape();
+        let triangles = triangulate(&polygon);
+        assert_eq!(triangles.len(), polygon.len() - 2);
+        let areas = signed_areas(&polygon, &triangles, Vector3::new(0.0, 0.0, 1.0));
+        assert!(areas.iter().all(|&area| area > 0.0), "{:?}", areas);
+        // A fan from the first corner would cover area outside the L.
+        assert_eq!(areas.iter().sum::<Float>(), 6.0);
+    }
+
+    #[test]
+    fn clockwise_polygons_in_other_planes_stay_clockwise() {
+        // The L shape turned to lie in the YZ plane, seen from -X.
+        let polygon = l_shape().iter().rev().map(|p| [0.0, p[0], p[1]]).collect::<Vec<_>>();
+        let triangles = triangulate(&polygon);
+        assert_eq!(triangles.len(), 4);
+        let areas = signed_areas(&polygon, &triangles, Vector3::new(-1.0, 0.0, 0.0));
+        assert!(areas.iter().all(|&area| area > 0.0), "{:?}", areas);
+        assert_eq!(areas.iter().sum::<Float>(), 6.0);
+    }
+
+    #[test]
+    fn degenerate_polygons_fall_back_to_a_fan() {
+        let line = [[0.0, 0.0, 0.0], [1.0, 1.0, 1.0], [2.0, 2.0, 2.0], [3.0, 3.0, 3.0], [4.0, 4.0, 4.0]];
+        assert_eq!(triangulate(&line), vec![[0, 1, 2], [0, 2, 3], [0, 3, 4]]);
+        let areas = signed_areas(&line, &triangulate(&line), Vector3::new(0.0, 0.0, 1.0));
+        assert!(areas.iter().all(|area| area.abs() < 1e-6));
+    }
+}
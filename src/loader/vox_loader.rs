@@ -0,0 +1,287 @@
+use std::io::{Read, Write, BufWriter};
+use std::fs::File;
+use std::error::Error;
+
+use cgmath::{Point3, Vector3};
+
+use model::block::{BlockType, BLOCK_INFOS};
+use world::structure::Structure;
+
+const MAGIC: &[u8; 4] = b"VOX ";
+const VERSION: u32 = 150;
+/// Largest size along any axis that MagicaVoxel supports.
+const MAX_SIZE: usize = 256;
+const PALETTE_SIZE: usize = 256;
+
+/// Reads MagicaVoxel ``.vox`` models into structures. Every palette color is
+/// mapped to the block type with the closest color.
+///
+/// MagicaVoxel uses the z axis as up, so its y and z axes are swapped, and y
+/// is flipped to keep the model from being mirrored.
+pub struct VoxLoader {
+    size: Option<Vector3<usize>>,
+    voxels: Vec<([usize; 3], u8)>,
+    palette: Vec<[u8; 3]>,
+}
+
+fn read_u32(bytes: &[u8], pos: usize) -> Result<u32, Box<dyn Error>> {
+    match bytes.get(pos..pos + 4) {
+        Some(b) => Ok(b[0] as u32 | (b[1] as u32) << 8 | (b[2] as u32) << 16 | (b[3] as u32) << 24),
+        None => Err(Box::<dyn Error>::from("Unexpected end of .vox file")),
+    }
+}
+
+fn write_u32<W: Write>(writer: &mut W, value: u32) -> Result<(), Box<dyn Error>> {
+    writer.write_all(&[value as u8, (value >> 8) as u8, (value >> 16) as u8, (value >> 24) as u8])?;
+    Ok(())
+}
+
+fn write_chunk<W: Write>(writer: &mut W, id: &[u8; 4], content: &[u8], children: &[u8]) -> Result<(), Box<dyn Error>> {
+    writer.write_all(id)?;
+    write_u32(writer, content.len() as u32)?;
+    write_u32(writer, children.len() as u32)?;
+    writer.write_all(content)?;
+    writer.write_all(children)?;
+    Ok(())
+}
+
+/// Checks that a model of ``size`` is neither empty nor larger than
+/// MagicaVoxel supports.
+pub fn check_size(size: &Vector3<usize>) -> Result<(), Box<dyn Error>> {
+    if (0..3).any(|i| size[i] == 0 || size[i] > MAX_SIZE) {
+        return Err(Box::<dyn Error>::from(format!("Models must be 1 to {} blocks along each axis, not {}x{}x{}",
+                                              MAX_SIZE, size.x, size.y, size.z)));
+    }
+    Ok(())
+}
+
+/// The palette MagicaVoxel uses for models without an ``RGBA`` chunk: a
+/// 6x6x6 color cube followed by red, green, blue and gray ramps.
+fn default_palette() -> Vec<[u8; 3]> {
+    let cube = [0xff, 0xcc, 0x99, 0x66, 0x33, 0x00];
+    let ramp = [0xee, 0xdd, 0xbb, 0xaa, 0x88, 0x77, 0x55, 0x44, 0x22, 0x11];
+    let mut palette = Vec::with_capacity(PALETTE_SIZE);
+    for &r in cube.iter() {
+        for &g in cube.iter() {
+            for &b in cube.iter() {
+                palette.push([r, g, b]);
+            }
+        }
+    }
+    // Black is part of the gray ramp instead.
+    palette.pop();
+    for channel in 0..3 {
+        for &v in ramp.iter() {
+            let mut color = [0; 3];
+            color[channel] = v;
+            palette.push(color);
+        }
+    }
+    for &v in ramp.iter() {
+        palette.push([v, v, v]);
+    }
+    palette.push([0, 0, 0]);
+    palette
+}
+
+impl VoxLoader {
+    pub fn new() -> VoxLoader {
+        VoxLoader { size: None, voxels: Vec::new(), palette: Vec::new() }
+    }
+
+    /// Reads the chunks in ``bytes[start..end]`` and the chunks nested in
+    /// them.
+    fn read_chunks(&mut self, bytes: &[u8], start: usize, end: usize) -> Result<(), Box<dyn Error>> {
+        let mut pos = start;
+        while pos < end {
+            let id = bytes.get(pos..pos + 4).ok_or_else(|| Box::<dyn Error>::from("Unexpected end of .vox file"))?;
+            let content_len = read_u32(bytes, pos + 4)? as usize;
+            let children_len = read_u32(bytes, pos + 8)? as usize;
+            let content_start = pos + 12;
+            let children_start = content_start + content_len;
+            pos = children_start + children_len;
+            if pos > end {
+                return Err(Box::<dyn Error>::from("Chunk exceeds the end of the .vox file"));
+            }
+            let content = &bytes[content_start..children_start];
+
+            match id {
+                b"MAIN" => self.read_chunks(bytes, children_start, pos)?,
+                // Only the first model of files with several models is read.
+                b"SIZE" if self.size.is_none() => {
+                    let size = [read_u32(content, 0)?, read_u32(content, 4)?, read_u32(content, 8)?];
+                    self.size = Some(Vector3::new(size[0] as usize, size[2] as usize, size[1] as usize));
+                },
+                b"XYZI" if self.voxels.is_empty() => {
+                    let count = read_u32(content, 0)? as usize;
+                    let data = content.get(4..4 + count * 4)
+                        .ok_or_else(|| Box::<dyn Error>::from("Truncated XYZI chunk in .vox file"))?;
+                    self.voxels = data.chunks(4)
+                        .map(|v| ([v[0] as usize, v[1] as usize, v[2] as usize], v[3]))
+                        .collect();
+                },
+                b"RGBA" => {
+                    if content.len() < PALETTE_SIZE * 4 {
+                        return Err(Box::<dyn Error>::from("Truncated RGBA chunk in .vox file"));
+                    }
+                    self.palette = content.chunks(4).take(PALETTE_SIZE).map(|c| [c[0], c[1], c[2]]).collect();
+                },
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    fn load_structure(&mut self, bytes: &[u8]) -> Result<Structure, Box<dyn Error>> {
+        if bytes.len() < 8 || &bytes[..4] != MAGIC {
+            return Err(Box::<dyn Error>::from("Not a .vox file"));
+        }
+
+        self.read_chunks(bytes, 8, bytes.len())?;
+        let size = self.size.ok_or_else(|| Box::<dyn Error>::from("No SIZE chunk in .vox file"))?;
+        check_size(&size)?;
+        if self.palette.is_empty() {
+            self.palette = default_palette();
+        }
+
+        // Color index ``i`` refers to palette entry ``i - 1``.
+        let block_types = self.palette.iter().map(|&color| BlockType::from_color(color)).collect::<Vec<_>>();
+        let mut structure = Structure::new(size, BlockType::Void(0));
+        for &(v, color_index) in &self.voxels {
+            if v[0] >= size.x || v[1] >= size.z || v[2] >= size.y || color_index == 0 {
+                continue;
+            }
+            let pos = Point3::new(v[0], v[2], size.z - 1 - v[1]);
+            structure.set(&pos, block_types[color_index as usize - 1]);
+        }
+        Ok(structure)
+    }
+
+    fn clear(&mut self) {
+        self.size = None;
+        self.voxels.clear();
+        self.palette.clear();
+    }
+
+    pub fn load_from_bytes(&mut self, bytes: &[u8]) -> Result<Structure, Box<dyn Error>> {
+        let result = self.load_structure(bytes)
+            .map_err(|e| Box::<dyn Error>::from(format!("Failed to parse .vox file: {}", e)));
+        self.clear();
+        result
+    }
+
+    pub fn load_from_file(&mut self, filename: &str) -> Result<Structure, Box<dyn Error>> {
+        let mut bytes = Vec::new();
+        File::open(filename)?.read_to_end(&mut bytes)?;
+        self.load_from_bytes(&bytes)
+    }
+}
+
+/// Writes ``structure`` as a MagicaVoxel model. The palette holds the color
+/// of every block type, with ``BlockType::Solid(i)`` at color index
+/// ``i + 1``.
+pub fn write_vox<W: Write>(structure: &Structure, writer: &mut W) -> Result<(), Box<dyn Error>> {
+    let size = structure.size();
+    check_size(&size)?;
+
+    let mut size_chunk = Vec::new();
+    for &s in [size.x, size.z, size.y].iter() {
+        write_u32(&mut size_chunk, s as u32)?;
+    }
+
+    let mut voxels = Vec::new();
+    for pos in structure.region_at(&Point3::new(0, 0, 0)).iter() {
+        if let BlockType::Solid(i) = structure.get(&pos) {
+            voxels.extend_from_slice(&[pos.x as u8, (size.z - 1 - pos.z) as u8, pos.y as u8, (i + 1) as u8]);
+        }
+    }
+    let mut xyzi_chunk = Vec::new();
+    write_u32(&mut xyzi_chunk, (voxels.len() / 4) as u32)?;
+    xyzi_chunk.extend_from_slice(&voxels);
+
+    let mut rgba_chunk = vec![0u8; PALETTE_SIZE * 4];
+    for (i, info) in BLOCK_INFOS.iter().enumerate().take(PALETTE_SIZE - 1) {
+        rgba_chunk[i * 4..i * 4 + 3].copy_from_slice(&info.color);
+        rgba_chunk[i * 4 + 3] = 255;
+    }
+
+    let mut children = Vec::new();
+    write_chunk(&mut children, b"SIZE", &size_chunk, &[])?;
+    write_chunk(&mut children, b"XYZI", &xyzi_chunk, &[])?;
+    write_chunk(&mut children, b"RGBA", &rgba_chunk, &[])?;
+
+    writer.write_all(MAGIC)?;
+    write_u32(writer, VERSION)?;
+    write_chunk(writer, b"MAIN", &[], &children)?;
+    Ok(())
+}
+
+pub fn save_vox(structure: &Structure, filename: &str) -> Result<(), Box<dyn Error>> {
+    let mut writer = BufWriter::new(File::create(filename)?);
+    write_vox(structure, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Point3, Vector3};
+
+    use model::block::{BlockType, BLOCK_INFOS};
+    use world::structure::Structure;
+    use super::{write_vox, VoxLoader, MAX_SIZE};
+
+    fn vox_bytes(structure: &Structure) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_vox(structure, &mut bytes).unwrap();
+        bytes
+    }
+
+    /// Replaces the size in a file written by ``write_vox``, whose SIZE
+    /// chunk content starts at byte 32.
+    fn set_size(bytes: &mut [u8], size: [u32; 3]) {
+        for i in 0..3 {
+            for j in 0..4 {
+                bytes[32 + i * 4 + j] = (size[i] >> (j * 8)) as u8;
+            }
+        }
+    }
+
+    #[test]
+    fn round_trips_every_block_type() {
+        let mut structure = Structure::new(Vector3::new(4, 3, 5), BlockType::Void(0));
+        for (i, pos) in structure.region_at(&Point3::new(0, 0, 0)).iter().enumerate() {
+            if i % 2 == 0 {
+                structure.set(&pos, BlockType::Solid(i / 2 % BLOCK_INFOS.len()));
+            }
+        }
+        let loaded = VoxLoader::new().load_from_bytes(&vox_bytes(&structure)).unwrap();
+        assert_eq!(loaded, structure);
+    }
+
+    #[test]
+    fn rejects_empty_models() {
+        let mut bytes = vox_bytes(&Structure::new(Vector3::new(1, 1, 1), BlockType::Solid(0)));
+        set_size(&mut bytes, [0, 1, 1]);
+        assert!(VoxLoader::new().load_from_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn rejects_oversized_models() {
+        let mut bytes = vox_bytes(&Structure::new(Vector3::new(1, 1, 1), BlockType::Solid(0)));
+        set_size(&mut bytes, [1, u32::MAX, 1]);
+        assert!(VoxLoader::new().load_from_bytes(&bytes).is_err());
+
+        let too_big = Structure::new(Vector3::new(1, MAX_SIZE + 1, 1), BlockType::Void(0));
+        assert!(write_vox(&too_big, &mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(VoxLoader::new().load_from_bytes(b"not a model").is_err());
+        let mut bytes = vox_bytes(&Structure::new(Vector3::new(2, 2, 2), BlockType::Solid(0)));
+        let len = bytes.len();
+        bytes.truncate(len - 10);
+        assert!(VoxLoader::new().load_from_bytes(&bytes).is_err());
+    }
+}
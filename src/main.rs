@@ -1,49 +1,54 @@
-#![feature(slice_patterns)]
 extern crate cgmath;
 #[macro_use]
 extern crate glium;
-#[macro_use]
-extern crate lazy_static;
 extern crate png;
 
-mod gl;
+#[macro_use]
 mod util;
+mod gl;
 mod game;
 mod model;
 mod world;
 mod loader;
+mod exporter;
 mod camera;
+mod input;
+mod config;
+mod hotbar;
+mod selection;
+mod console;
+mod assets;
 
 use glium::{glutin, Surface};
 use cgmath::{Point3, Matrix4, SquareMatrix, EuclideanSpace};
 
 use game::Game;
-use gl::{build_vertex_buffer, build_index_buffer};
+use gl::{build_vertex_buffer, build_index_buffer, build_material_textures, build_image_texture, load_program, reload_program};
 use util::types::Float;
-use model::meshes::Meshes;
-
-lazy_static! {
-    static ref MESHES: Meshes = Meshes::load();
-}
+use model::meshes::{self, Meshes};
+use model::block::BlockType;
+use model::atlas::{self, MESH_TILE};
+use config::settings::Settings;
+use util::constants::SETTINGS_FILE;
 
 fn main() {
+    let settings = Settings::load(SETTINGS_FILE);
+
     let mut events_loop = glutin::EventsLoop::new();
-    let window = glutin::WindowBuilder::new().with_dimensions(640, 480);
-    let context = glutin::ContextBuilder::new().with_vsync(true);
+    let window = glutin::WindowBuilder::new().with_dimensions(settings.window_width, settings.window_height);
+    let context = glutin::ContextBuilder::new().with_vsync(settings.vsync);
     let display = glium::Display::new(window, context, &events_loop).unwrap();
     let _ = display.gl_window().set_cursor_state(glutin::CursorState::Grab);
-    let _ = display.gl_window().set_cursor(glutin::MouseCursor::NoneCursor);
+    display.gl_window().set_cursor(glutin::MouseCursor::NoneCursor);
 
-    let mut game = Game::new(&display);
-    game.world_mut().add_block(&Point3::origin());
+    let mut game = Game::new(&display, settings);
+    game.world_mut().add_block(&Point3::origin(), BlockType::Solid(0));
 
     // compiling shaders and linking them together
-    let program = program!(&display,
-        330 => {
-            vertex: include_str!("shader/block.vertex"),
-            fragment: include_str!("shader/block.fragment"),
-        },
-    ).unwrap();
+    let mut program = {
+        let (assets, console) = game.assets_and_console();
+        load_program(&display, assets, "block", console)
+    };
 
     // draw parameters
     let params = glium::DrawParameters {
@@ -59,37 +64,67 @@ fn main() {
     let model_matrix = Matrix4::identity();
     let mut stop = false;
 
-    let decoder = png::Decoder::new(std::io::Cursor::new(&include_bytes!("../textures/minecraft.png")[..]));
-    let (info, mut reader) = decoder.read_info().unwrap();
-    let mut tex_buf = vec![0; info.buffer_size()];
-    reader.next_frame(&mut tex_buf).unwrap();
-
-    let raw_image = glium::texture::RawImage2d::from_raw_rgba_reversed(&tex_buf, (info.width, info.height));
-    let texture = glium::texture::SrgbTexture2d::new(&display, raw_image).unwrap();
+    let mut texture = build_image_texture(&display, &game.block_atlas().image);
+    let mut meshes = {
+        let (assets, console) = game.assets_and_console();
+        Meshes::load(assets, console)
+    };
+    let mut material_textures = build_material_textures(&display, meshes.block());
 
     while !stop {
-        game.camera_mut().update();
+        game.update();
+        let changed_assets = game.reload_changed_assets();
+        if !changed_assets.is_empty() {
+            if atlas::sources_changed(&changed_assets) {
+                texture = build_image_texture(&display, &game.block_atlas().image);
+            }
+            let (assets, console) = game.assets_and_console();
+            reload_program(&display, assets, "block", &changed_assets, &mut program, console);
+            if meshes::sources_changed(&changed_assets) {
+                match meshes.reload(assets) {
+                    Ok(()) => {
+                        material_textures = build_material_textures(&display, meshes.block());
+                        console.print("Reloaded the block model");
+                    },
+                    Err(e) => console.print(&format!("Failed to reload the block model, keeping the old one: {}", e)),
+                }
+            }
+        }
         let view_matrix = game.camera().view();
         let projection_matrix = game.camera().perspective();
         let mvp_matrix: [[Float; 4]; 4] = std::convert::Into::into(projection_matrix * view_matrix * model_matrix);
-        let uniforms = uniform! {
-            mvp_matrix: mvp_matrix,
-            tex: texture.sampled().magnify_filter(glium::uniforms::MagnifySamplerFilter::Nearest),
-        };
 
         let mut target = display.draw();
-        target.clear_color_srgb_and_depth((66.0/255.0, 196.0/255.0, 247.0/255.0, 1.0), 1.0);
+        let (r, g, b) = game.sky_color();
+        target.clear_color_srgb_and_depth((r, g, b, 1.0), 1.0);
         {
-            let vbuf = build_vertex_buffer(&display, &MESHES.block);
-            let ibuf = build_index_buffer(&display, &MESHES.block);
+            let vbuf = build_vertex_buffer(&display, meshes.block());
+            let ibuf = build_index_buffer(&display, meshes.block());
+            let tile_size = game.block_atlas().uvs.tile_size();
             if let Some(translations_buf) = game.world_mut().translations() {
-                target.draw((&vbuf, translations_buf.per_instance().unwrap()),
-                             &ibuf,
-                             &program,
-                             &uniforms,
-                             &params).unwrap();
+                // Draw every material of the mesh with its own color and
+                // texture, falling back to the tiles of the block atlas.
+                for range in meshes.block().draw_ranges() {
+                    let material = range.material.map(|i| &meshes.block().materials[i]);
+                    let material_texture = range.material.and_then(|i| material_textures[i].as_ref());
+                    let uniforms = uniform! {
+                        mvp_matrix: mvp_matrix,
+                        tex: material_texture.unwrap_or(&texture).sampled()
+                            .magnify_filter(glium::uniforms::MagnifySamplerFilter::Nearest),
+                        diffuse_color: material.map(|m| m.diffuse_color).unwrap_or([1.0; 3]),
+                        mesh_tile: MESH_TILE,
+                        tile_size: tile_size,
+                        use_atlas: material_texture.is_none(),
+                    };
+                    target.draw((&vbuf, translations_buf.per_instance().unwrap()),
+                                 ibuf.slice(range.start..range.start + range.count).unwrap(),
+                                 &program,
+                                 &uniforms,
+                                 &params).unwrap();
+                }
             }
-            game.overlay().draw(&mut target); 
+            game.draw_outline(&mut target, mvp_matrix);
+            game.draw_overlay(&mut target, &texture, meshes.block());
             target.finish().unwrap();
         }
 
@@ -98,10 +133,7 @@ fn main() {
                 match event {
                     // Break from the main loop when the window is closed.
                     glutin::WindowEvent::Closed => stop = true,
-                    ev => { 
-                        game.camera_mut().process_input(&ev);
-                        game.process_input(&ev);
-                    },
+                    ev => game.process_input(&ev),
                 }
             }
         });
@@ -1,7 +1,11 @@
+use std::mem;
+
 use cgmath::{Vector3, Point3};
 
 use util::types::Float;
 
+/// An axis-aligned bounding box.
+#[allow(clippy::upper_case_acronyms)]
 pub trait AABB {
     fn get_min(&self) -> Point3<Float>;
     fn get_max(&self) -> Point3<Float>;
@@ -22,14 +26,10 @@ fn clip_ray<T>(axis: Axis, ray_start: &Point3<Float>, ray_stop: &Point3<Float>,
     let mut f_dim_high = (aabb_box.get_max()[axis] + aabb_box_pos[axis] - ray_start[axis]) / (ray_stop[axis] - ray_start[axis]);
 
     if f_dim_high < f_dim_low {
-        let tmp  = f_dim_high;
-        f_dim_high = f_dim_low;
-        f_dim_low = tmp;
+        mem::swap(&mut f_dim_low, &mut f_dim_high);
     }
 
-    if f_dim_high < f_low {
-        None
-    } else if f_dim_low > f_high {
+    if f_dim_high < f_low || f_dim_low > f_high {
         None
     } else {
         let f_low = f_dim_low.max(f_low);
@@ -0,0 +1,265 @@
+use std::error::Error;
+
+use assets::{AssetManager, ATLAS_ASSET};
+use console::Console;
+use model::block::{BlockInfo, BlockType, BLOCK_INFOS, ATLAS_TILES};
+use util::image::Image;
+use util::types::Float;
+
+/// Pixels around every tile that repeat its outermost pixels, so filtering
+/// near the edge of a tile does not pick up its neighbors.
+pub const PADDING: u32 = 2;
+
+/// Directory of the block textures in a resource directory.
+pub const BLOCK_TEXTURE_DIR: &str = "blocks";
+
+/// Texture coordinates of the tile that the block mesh is mapped to, as
+/// minimum and size: the top left tile of the built-in atlas.
+pub const MESH_TILE: [Float; 4] = [0.0, 1.0 - 1.0 / ATLAS_TILES as Float, 1.0 / ATLAS_TILES as Float, 1.0 / ATLAS_TILES as Float];
+
+/// A face of a block, by the direction it faces.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Face {
+    /// +x
+    East,
+    /// -x
+    West,
+    /// +y
+    Top,
+    /// -y
+    Bottom,
+    /// +z
+    South,
+    /// -z
+    North,
+}
+
+pub const FACES: [Face; 6] = [Face::East, Face::West, Face::Top, Face::Bottom, Face::South, Face::North];
+
+impl Face {
+    /// The face whose outward normal is ``normal``, which must point along
+    /// one of the axes.
+    pub fn from_normal(normal: &[i32; 3]) -> Face {
+        match *normal {
+            [x, _, _] if x > 0 => Face::East,
+            [x, _, _] if x < 0 => Face::West,
+            [_, y, _] if y > 0 => Face::Top,
+            [_, y, _] if y < 0 => Face::Bottom,
+            [_, _, z] if z > 0 => Face::South,
+            _ => Face::North,
+        }
+    }
+
+    /// The name that block textures for the face end with.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Face::East => "east",
+            Face::West => "west",
+            Face::Top => "top",
+            Face::Bottom => "bottom",
+            Face::South => "south",
+            Face::North => "north",
+        }
+    }
+
+    pub fn is_side(&self) -> bool {
+        *self != Face::Top && *self != Face::Bottom
+    }
+
+    fn index(&self) -> usize {
+        FACES.iter().position(|face| face == self).unwrap()
+    }
+}
+
+/// Names of the assets that can provide the texture of ``face`` of a block,
+/// most specific first: ``blocks/<name>_<face>.png``, for the four sides
+/// ``blocks/<name>_side.png``, and ``blocks/<name>.png``.
+pub fn texture_names(info: &BlockInfo, face: Face) -> Vec<String> {
+    let mut names = vec![format!("{}/{}_{}.png", BLOCK_TEXTURE_DIR, info.name, face.name())];
+    if face.is_side() {
+        names.push(format!("{}/{}_side.png", BLOCK_TEXTURE_DIR, info.name));
+    }
+    names.push(format!("{}/{}.png", BLOCK_TEXTURE_DIR, info.name));
+    names
+}
+
+/// Names of all assets the block atlas is stitched from, including the
+/// built-in atlas that missing textures are taken from.
+pub fn source_names() -> Vec<String> {
+    let mut names = vec![ATLAS_ASSET.to_string()];
+    for info in BLOCK_INFOS {
+        for &face in FACES.iter() {
+            for name in texture_names(info, face) {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+    }
+    names
+}
+
+/// Whether any of the ``changed`` assets is one the block atlas is stitched
+/// from.
+pub fn sources_changed(changed: &[String]) -> bool {
+    let sources = source_names();
+    changed.iter().any(|name| sources.contains(name))
+}
+
+/// Where the texture of every face of every block type is in the atlas.
+#[derive(Debug, Clone)]
+pub struct UvTable {
+    tile_size: [Float; 2],
+    /// Lower left texture coordinates of the tiles, by block index and face.
+    origins: Vec<[[Float; 2]; 6]>,
+}
+
+impl UvTable {
+    /// Creates a table that maps everything to the lower left corner.
+    pub fn new() -> UvTable {
+        UvTable { tile_size: [0.0; 2], origins: Vec::new() }
+    }
+
+    /// Size of a tile in texture coordinates, without padding.
+    pub fn tile_size(&self) -> [Float; 2] {
+        self.tile_size
+    }
+
+    /// Lower left texture coordinates of the tile of ``face`` of
+    /// ``block_type``. Void blocks have no tile and get ``[0.0, 0.0]``.
+    pub fn origin(&self, block_type: BlockType, face: Face) -> [Float; 2] {
+        match block_type {
+            BlockType::Solid(i) => self.origins.get(i).map(|origins| origins[face.index()]).unwrap_or([0.0; 2]),
+            BlockType::Void(_) => [0.0; 2],
+        }
+    }
+
+    /// Lower left and upper right texture coordinates of the tile of
+    /// ``face`` of ``block_type``.
+    pub fn rect(&self, block_type: BlockType, face: Face) -> ([Float; 2], [Float; 2]) {
+        let min = self.origin(block_type, face);
+        (min, [min[0] + self.tile_size[0], min[1] + self.tile_size[1]])
+    }
+}
+
+/// Where the image of a tile comes from.
+#[derive(Debug, PartialEq)]
+enum Source {
+    Pack(String),
+    /// Column and row in the built-in atlas.
+    Builtin(u32, u32),
+}
+
+/// The block textures stitched into one image, which is drawn with texture
+/// coordinates from ``uvs``.
+pub struct TextureAtlas {
+    pub image: Image,
+    pub uvs: UvTable,
+}
+
+impl TextureAtlas {
+    /// Stitches the block textures from the resource directories of
+    /// ``assets`` into an atlas. Every face uses the first of its
+    /// ``texture_names`` that exists, or else the block's tile of the
+    /// built-in atlas. Faces that share a texture share a tile, and all
+    /// tiles are scaled to the size of the first texture from a resource
+    /// directory. Textures that fail to load are reported on ``console``.
+    pub fn stitch(assets: &AssetManager, console: &mut Console) -> Result<TextureAtlas, Box<dyn Error>> {
+        let builtin = assets.load_with(ATLAS_ASSET, |bytes, _| Image::decode_png(bytes), console)?;
+        let builtin_tile_size = (builtin.width / ATLAS_TILES, builtin.height / ATLAS_TILES);
+        let builtin_tile = |column: u32, row: u32| {
+            builtin.sub_image(column * builtin_tile_size.0, row * builtin_tile_size.1, builtin_tile_size.0, builtin_tile_size.1)
+        };
+
+        let mut sources = Vec::new();
+        let mut tiles: Vec<Image> = Vec::new();
+        let mut tile_size = None;
+        let mut face_tiles = Vec::new();
+        for info in BLOCK_INFOS {
+            let mut indices = [0; 6];
+            for (i, &face) in FACES.iter().enumerate() {
+                let source = texture_names(info, face).into_iter()
+                    .find(|name| assets.find(name).is_some())
+                    .map(Source::Pack)
+                    .unwrap_or(Source::Builtin(info.tile.0, info.tile.1));
+                let known_index = sources.iter().position(|known| *known == source);
+                indices[i] = match known_index {
+                    Some(index) => index,
+                    None => {
+                        let tile = match source {
+                            Source::Pack(ref name) => match assets.build(name, |bytes, _| Image::decode_png(bytes)) {
+                                Ok(image) => {
+                                    tile_size = tile_size.or(Some((image.width, image.height)));
+                                    image
+                                },
+                                Err(e) => {
+                                    console.print(&format!("Failed to load {}, using the built-in texture: {}", name, e));
+                                    builtin_tile(info.tile.0, info.tile.1)
+                                },
+                            },
+                            Source::Builtin(column, row) => builtin_tile(column, row),
+                        };
+                        tiles.push(tile);
+                        sources.push(source);
+                        tiles.len() - 1
+                    },
+                };
+            }
+            face_tiles.push(indices);
+        }
+
+        let (tile_width, tile_height) = tile_size.unwrap_or(builtin_tile_size);
+        if tile_width == 0 || tile_height == 0 {
+            return Err(Box::<dyn Error>::from("Block textures must not be empty"));
+        }
+        let columns = (tiles.len() as f64).sqrt().ceil() as u32;
+        let rows = (tiles.len() as u32).div_ceil(columns);
+        let (cell_width, cell_height) = (tile_width + 2 * PADDING, tile_height + 2 * PADDING);
+        let mut image = Image::new(columns * cell_width, rows * cell_height);
+
+        let mut tile_origins = Vec::new();
+        for (i, tile) in tiles.iter().enumerate() {
+            let resized;
+            let tile = if tile.width != tile_width || tile.height != tile_height {
+                resized = tile.resized(tile_width, tile_height);
+                &resized
+            } else {
+                tile
+            };
+            let x = (i as u32 % columns) * cell_width + PADDING;
+            let y = (i as u32 / columns) * cell_height + PADDING;
+            blit_padded(&mut image, tile, x, y);
+            // Image rows go down, texture coordinates go up.
+            tile_origins.push([
+                x as Float / image.width as Float,
+                1.0 - (y + tile_height) as Float / image.height as Float,
+            ]);
+        }
+
+        let origins = face_tiles.iter().map(|indices| {
+            let mut origins = [[0.0; 2]; 6];
+            for i in 0..6 {
+                origins[i] = tile_origins[indices[i]];
+            }
+            origins
+        }).collect();
+        let uvs = UvTable {
+            tile_size: [tile_width as Float / image.width as Float, tile_height as Float / image.height as Float],
+            origins,
+        };
+        Ok(TextureAtlas { image, uvs })
+    }
+}
+
+/// Copies ``tile`` into ``atlas`` with its top left corner at ``x``, ``y``
+/// and extends its border pixels outwards by ``PADDING``.
+fn blit_padded(atlas: &mut Image, tile: &Image, x: u32, y: u32) {
+    let padding = PADDING as i64;
+    for dy in -padding..tile.height as i64 + padding {
+        for dx in -padding..tile.width as i64 + padding {
+            let source_x = dx.max(0).min(tile.width as i64 - 1) as u32;
+            let source_y = dy.max(0).min(tile.height as i64 - 1) as u32;
+            atlas.set_pixel((x as i64 + dx) as u32, (y as i64 + dy) as u32, tile.pixel(source_x, source_y));
+        }
+    }
+}
@@ -1,16 +1,91 @@
 use cgmath::Point3;
 
-use model::Model;
-use model::mesh::Mesh;
 use model::aabb::AABB;
 use util::types::Float;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum BlockType {
     Void(usize),
     Solid(usize),
 }
 
+/// Static information about a solid block type. ``BlockType::Solid(i)``
+/// refers to the i-th entry of ``BLOCK_INFOS``.
+#[derive(Debug)]
+pub struct BlockInfo {
+    pub name: &'static str,
+    /// Column and row of the block texture in the built-in texture atlas,
+    /// used for the faces that no resource directory has a texture for.
+    pub tile: (u32, u32),
+    /// Average color of the block texture, used to find the block type that
+    /// best matches a color.
+    pub color: [u8; 3],
+}
+
+/// Number of tiles along each side of the built-in texture atlas.
+pub const ATLAS_TILES: u32 = 16;
+
+pub const BLOCK_INFOS: &[BlockInfo] = &[
+    BlockInfo { name: "dirt", tile: (2, 0), color: [134, 96, 67] },
+    BlockInfo { name: "stone", tile: (1, 0), color: [125, 125, 125] },
+    BlockInfo { name: "grass", tile: (0, 0), color: [117, 176, 73] },
+    BlockInfo { name: "planks", tile: (4, 0), color: [157, 128, 79] },
+    BlockInfo { name: "bricks", tile: (7, 0), color: [156, 110, 97] },
+    BlockInfo { name: "cobblestone", tile: (0, 1), color: [117, 117, 117] },
+    BlockInfo { name: "sand", tile: (2, 1), color: [218, 210, 158] },
+    BlockInfo { name: "gravel", tile: (3, 1), color: [136, 126, 126] },
+    BlockInfo { name: "log", tile: (4, 1), color: [102, 81, 50] },
+    BlockInfo { name: "iron_block", tile: (6, 1), color: [230, 230, 230] },
+    BlockInfo { name: "gold_block", tile: (7, 1), color: [249, 236, 78] },
+    BlockInfo { name: "diamond_block", tile: (8, 1), color: [100, 219, 214] },
+    BlockInfo { name: "obsidian", tile: (5, 2), color: [20, 18, 30] },
+];
+
+impl BlockType {
+    /// Looks up a block type by its name in ``BLOCK_INFOS`` or its index.
+    /// The name ``air`` refers to the empty block.
+    pub fn from_name(name: &str) -> Option<BlockType> {
+        if name == "air" {
+            return Some(BlockType::Void(0));
+        }
+
+        BLOCK_INFOS.iter().position(|info| info.name == name)
+            .or_else(|| name.parse::<usize>().ok().filter(|&i| i < BLOCK_INFOS.len()))
+            .map(BlockType::Solid)
+    }
+
+    /// The name that ``from_name`` looks the block type up by.
+    pub fn name(&self) -> &'static str {
+        self.info().map(|info| info.name).unwrap_or("air")
+    }
+
+    /// Like ``==``, but treats all void blocks as the same.
+    pub fn same_kind(&self, other: &BlockType) -> bool {
+        match (*self, *other) {
+            (BlockType::Void(_), BlockType::Void(_)) => true,
+            (a, b) => a == b,
+        }
+    }
+
+    /// The solid block type whose color is closest to ``color``.
+    pub fn from_color(color: [u8; 3]) -> BlockType {
+        let distance = |info: &BlockInfo| {
+            (0..3).map(|i| (info.color[i] as i32 - color[i] as i32).pow(2)).sum::<i32>()
+        };
+        let (index, _) = BLOCK_INFOS.iter().enumerate()
+            .min_by_key(|&(_, info)| distance(info))
+            .unwrap();
+        BlockType::Solid(index)
+    }
+
+    pub fn info(&self) -> Option<&'static BlockInfo> {
+        match *self {
+            BlockType::Solid(i) => BLOCK_INFOS.get(i),
+            BlockType::Void(_) => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Block {
     block_type: BlockType,
@@ -20,7 +95,7 @@ pub const DIM: Float = 2.0;
 
 impl Block {
     pub fn new(block_type: BlockType) -> Block {
-        Block { block_type: block_type }
+        Block { block_type }
     }
 
     pub fn get_type(&self) -> BlockType {
@@ -28,12 +103,6 @@ impl Block {
     }
 }
 
-impl Model for Block {
-    fn get_mesh(&self) -> &Mesh {
-        &::MESHES.block
-    }
-}
-
 impl AABB for Block {
     fn get_min(&self) -> Point3<Float> {
         Point3::from([-DIM / 2.0; 3])
@@ -0,0 +1,23 @@
+use std::path::PathBuf;
+
+use util::types::Float;
+
+/// Surface properties of (part of) a mesh, as read from a material library.
+#[derive(Debug, Clone)]
+pub struct Material {
+    pub name: String,
+    pub diffuse_color: [Float; 3],
+    /// Path of the diffuse texture, relative to the working directory.
+    pub diffuse_texture: Option<PathBuf>,
+}
+
+impl Material {
+    /// Creates a white, untextured material.
+    pub fn new(name: &str) -> Material {
+        Material {
+            name: name.to_string(),
+            diffuse_color: [1.0; 3],
+            diffuse_texture: None,
+        }
+    }
+}
@@ -1,16 +1,407 @@
+use std::collections::HashMap;
+
+use cgmath::{Vector3, InnerSpace};
+
+use util::types::{Float, GLIndex};
 use gl::vertex::Vertex;
+use model::material::Material;
+
+/// How normals are generated for meshes that do not have them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NormalMode {
+    /// Every triangle gets the normal of its face.
+    Flat,
+    /// Normals are averaged over the faces that share a position, weighted
+    /// by the angle of each face at the vertex. Faces whose normals differ by
+    /// more than ``crease_angle`` radians keep a sharp edge between them.
+    Smooth { crease_angle: Float },
+}
+
+fn vector(v: [Float; 3]) -> Vector3<Float> {
+    Vector3::new(v[0], v[1], v[2])
+}
+
+/// Bits of a vector that can be used as a hash key. Adding zero turns
+/// negative zeros into positive ones, so both end up with the same key.
+fn key(v: [Float; 3]) -> [u32; 3] {
+    [(v[0] + 0.0).to_bits(), (v[1] + 0.0).to_bits(), (v[2] + 0.0).to_bits()]
+}
+
+fn normalize_or_zero(v: Vector3<Float>) -> Vector3<Float> {
+    if v.magnitude2() > 0.0 { v.normalize() } else { v }
+}
+
+/// Angle between the edges from ``a`` to ``b`` and from ``a`` to ``c``, or
+/// zero if either edge has no length.
+fn corner_angle(a: Vector3<Float>, b: Vector3<Float>, c: Vector3<Float>) -> Float {
+    let (ab, ac) = (b - a, c - a);
+    if ab.magnitude2() == 0.0 || ac.magnitude2() == 0.0 {
+        return 0.0;
+    }
+    ab.normalize().dot(ac.normalize()).clamp(-1.0, 1.0).acos()
+}
+
+/// A range of a mesh's indices that is drawn with a single material.
+#[derive(Debug, Clone)]
+pub struct SubMesh {
+    /// Index into the materials of the mesh, if the range has a material.
+    pub material: Option<usize>,
+    pub start: usize,
+    pub count: usize,
+}
 
 #[derive(Debug)]
 pub struct Mesh {
     pub vertex_info: Vec<Vertex>,
     pub indices: Vec<u32>,
+    pub materials: Vec<Material>,
+    /// Ranges of ``indices`` per material. Meshes without submeshes are drawn
+    /// as a whole without a material.
+    pub submeshes: Vec<SubMesh>,
 }
 
 impl Mesh {
     pub fn new() -> Mesh {
         Mesh { 
             vertex_info: Vec::new(), 
-            indices: Vec::new()
+            indices: Vec::new(),
+            materials: Vec::new(),
+            submeshes: Vec::new(),
+        }
+    }
+
+    /// Ends the current submesh and starts a new one with ``material`` for
+    /// the indices that are added after this call.
+    pub fn begin_submesh(&mut self, material: Option<usize>) {
+        self.end_submesh();
+        self.submeshes.push(SubMesh { material, start: self.indices.len(), count: 0 });
+    }
+
+    /// Ends the current submesh at the end of the indices. Submeshes without
+    /// any indices are dropped.
+    pub fn end_submesh(&mut self) {
+        let end = self.indices.len();
+        if let Some(last) = self.submeshes.last_mut() {
+            last.count = end - last.start;
+        }
+        if self.submeshes.last().map(|last| last.count == 0).unwrap_or(false) {
+            self.submeshes.pop();
+        }
+    }
+
+    /// The ranges to draw, with the whole mesh as a single range if it has
+    /// no submeshes.
+    pub fn draw_ranges(&self) -> Vec<SubMesh> {
+        if self.submeshes.is_empty() {
+            vec![SubMesh { material: None, start: 0, count: self.indices.len() }]
+        } else {
+            self.submeshes.clone()
+        }
+    }
+
+    /// Whether any vertex lacks a normal, which is stored as a zero vector.
+    pub fn has_missing_normals(&self) -> bool {
+        self.vertex_info.iter().any(|vertex| vertex.norm == [0.0; 3])
+    }
+
+    fn triangle(&self, t: usize) -> [Vector3<Float>; 3] {
+        let i = &self.indices[t * 3..t * 3 + 3];
+        [vector(self.vertex_info[i[0] as usize].pos),
+         vector(self.vertex_info[i[1] as usize].pos),
+         vector(self.vertex_info[i[2] as usize].pos)]
+    }
+
+    /// Unit normals of the triangles, following their counter-clockwise
+    /// winding. Triangles without an area get a zero vector.
+    fn face_normals(&self) -> Vec<Vector3<Float>> {
+        (0..self.indices.len() / 3).map(|t| {
+            let [a, b, c] = self.triangle(t);
+            normalize_or_zero((b - a).cross(c - a))
+        }).collect()
+    }
+
+    /// Replaces the normal of every corner of every triangle by
+    /// ``corner_normal(corner)``, where a corner is a position in
+    /// ``indices``. Vertices are split where their corners get different
+    /// normals.
+    fn rebuild_normals<F>(&mut self, corner_normal: F)
+        where F: Fn(usize) -> Vector3<Float>
+    {
+        let corner_count = self.indices.len() / 3 * 3;
+        let mut vertex_info = Vec::with_capacity(self.vertex_info.len());
+        let mut vertex_map = HashMap::new();
+        let mut indices = Vec::with_capacity(corner_count);
+        for corner in 0..corner_count {
+            let index = self.indices[corner];
+            let normal: [Float; 3] = corner_normal(corner).into();
+            let new_index = *vertex_map.entry((index, key(normal))).or_insert_with(|| {
+                let mut vertex = self.vertex_info[index as usize];
+                vertex.norm = normal;
+                vertex_info.push(vertex);
+                (vertex_info.len() - 1) as GLIndex
+            });
+            indices.push(new_index);
+        }
+        self.vertex_info = vertex_info;
+        self.indices = indices;
+    }
+
+    /// The normal of every corner of every triangle according to ``mode``.
+    fn corner_normals(&self, mode: NormalMode) -> Vec<Vector3<Float>> {
+        let face_normals = self.face_normals();
+        let corner_count = self.indices.len() / 3 * 3;
+        let crease_angle = match mode {
+            NormalMode::Flat => return (0..corner_count).map(|corner| face_normals[corner / 3]).collect(),
+            NormalMode::Smooth { crease_angle } => crease_angle,
+        };
+
+        let min_cos = crease_angle.cos();
+        let mut corner_angles = Vec::with_capacity(corner_count);
+        let mut corners_at = HashMap::new();
+        for corner in 0..corner_count {
+            let [a, b, c] = self.triangle(corner / 3);
+            corner_angles.push(match corner % 3 {
+                0 => corner_angle(a, b, c),
+                1 => corner_angle(b, c, a),
+                _ => corner_angle(c, a, b),
+            });
+            let pos = self.vertex_info[self.indices[corner] as usize].pos;
+            corners_at.entry(key(pos)).or_insert_with(Vec::new).push(corner);
+        }
+
+        (0..corner_count).map(|corner| {
+            let face_normal = face_normals[corner / 3];
+            let pos = key(self.vertex_info[self.indices[corner] as usize].pos);
+            let mut normal = Vector3::new(0.0, 0.0, 0.0);
+            for &other in &corners_at[&pos] {
+                let other_normal = face_normals[other / 3];
+                if face_normal.dot(other_normal) >= min_cos {
+                    normal += other_normal * corner_angles[other];
+                }
+            }
+            // A corner that has no angle itself can end up without normal.
+            if normal.magnitude2() > 0.0 { normal.normalize() } else { face_normal }
+        }).collect()
+    }
+
+    /// Computes normals according to ``mode`` for the vertices that lack
+    /// one. Vertices that are shared by faces with different normals are
+    /// split, and vertices that have a normal keep it.
+    pub fn compute_missing_normals(&mut self, mode: NormalMode) {
+        let normals = self.corner_normals(mode);
+        let existing = self.indices.iter().map(|&i| self.vertex_info[i as usize].norm).collect::<Vec<_>>();
+        self.rebuild_normals(|corner| {
+            if existing[corner] == [0.0; 3] { normals[corner] } else { vector(existing[corner]) }
+        });
+    }
+
+    /// Computes a tangent for every vertex from the texture coordinates, for
+    /// normal mapping. The fourth component is the handedness of the
+    /// tangent space: the bitangent is ``cross(normal, tangent) * w``.
+    // No shader does normal mapping yet, so only the tests call this.
+    #[allow(dead_code)]
+    pub fn compute_tangents(&self) -> Vec<[Float; 4]> {
+        let mut tangents = vec![Vector3::new(0.0, 0.0, 0.0); self.vertex_info.len()];
+        let mut bitangents = tangents.clone();
+        for face in self.indices.chunks(3).filter(|face| face.len() == 3) {
+            let v = [self.vertex_info[face[0] as usize], self.vertex_info[face[1] as usize], self.vertex_info[face[2] as usize]];
+            let (e1, e2) = (vector(v[1].pos) - vector(v[0].pos), vector(v[2].pos) - vector(v[0].pos));
+            let (s1, t1) = (v[1].tex_coords[0] - v[0].tex_coords[0], v[1].tex_coords[1] - v[0].tex_coords[1]);
+            let (s2, t2) = (v[2].tex_coords[0] - v[0].tex_coords[0], v[2].tex_coords[1] - v[0].tex_coords[1]);
+            let det = s1 * t2 - s2 * t1;
+            // Triangles without texture area have no tangent space.
+            if det == 0.0 {
+                continue;
+            }
+            let tangent = (e1 * t2 - e2 * t1) / det;
+            let bitangent = (e2 * s1 - e1 * s2) / det;
+            for &i in face {
+                tangents[i as usize] += tangent;
+                bitangents[i as usize] += bitangent;
+            }
+        }
+
+        self.vertex_info.iter().enumerate().map(|(i, vertex)| {
+            let normal = vector(vertex.norm);
+            // Make the tangent orthogonal to the normal, or pick any vector
+            // that is if there is no tangent.
+            let mut tangent = normalize_or_zero(tangents[i] - normal * normal.dot(tangents[i]));
+            if tangent.magnitude2() == 0.0 {
+                let axis = if normal.x.abs() < 0.9 { Vector3::new(1.0, 0.0, 0.0) } else { Vector3::new(0.0, 1.0, 0.0) };
+                tangent = normalize_or_zero(axis - normal * normal.dot(axis));
+            }
+            let w = if normal.cross(tangent).dot(bitangents[i]) < 0.0 { -1.0 } else { 1.0 };
+            [tangent.x, tangent.y, tangent.z, w]
+        }).collect()
+    }
+
+    /// Merges vertices whose position, texture coordinates and normal are
+    /// equal after snapping them to a grid with cells of ``epsilon``, and
+    /// drops vertices that are not used by any triangle. Returns the number
+    /// of vertices that were removed.
+    pub fn weld_vertices(&mut self, epsilon: Float) -> usize {
+        let snap = |v: Float| if epsilon > 0.0 { (v / epsilon).round() as i64 } else { v.to_bits() as i64 };
+        let mut vertex_info = Vec::with_capacity(self.vertex_info.len());
+        let mut vertex_map = HashMap::new();
+        for index in self.indices.iter_mut() {
+            let vertex = self.vertex_info[*index as usize];
+            let attributes = vertex.pos.iter().chain(vertex.tex_coords.iter()).chain(vertex.norm.iter())
+                .map(|&v| snap(v + 0.0)).collect::<Vec<_>>();
+            *index = *vertex_map.entry(attributes).or_insert_with(|| {
+                vertex_info.push(vertex);
+                (vertex_info.len() - 1) as GLIndex
+            });
+        }
+        let removed = self.vertex_info.len() - vertex_info.len();
+        self.vertex_info = vertex_info;
+        removed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Vector3, InnerSpace};
+
+    use util::types::Float;
+    use gl::vertex::Vertex;
+    use super::{Mesh, NormalMode, vector};
+
+    /// A unit cube without normals whose eight corners are shared by all
+    /// faces around them.
+    fn cube() -> Mesh {
+        let mut mesh = Mesh::new();
+        for i in 0..8 {
+            let pos = [(i & 1) as Float, (i >> 1 & 1) as Float, (i >> 2 & 1) as Float];
+            mesh.vertex_info.push(Vertex::new(pos, [0.0, 0.0], [0.0; 3]));
+        }
+        let quads = [[0, 4, 6, 2], [1, 3, 7, 5], [0, 1, 5, 4], [2, 6, 7, 3], [0, 2, 3, 1], [4, 5, 7, 6]];
+        for q in &quads {
+            mesh.indices.extend_from_slice(&[q[0], q[1], q[2], q[0], q[2], q[3]]);
+        }
+        mesh
+    }
+
+    /// A square in the XY plane facing +Z, with texture coordinates given
+    /// for every corner.
+    fn square(tex_coords: [[Float; 2]; 4]) -> Mesh {
+        let mut mesh = Mesh::new();
+        let positions = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [1.0, 1.0, 0.0], [0.0, 1.0, 0.0]];
+        for (&pos, &tex) in positions.iter().zip(tex_coords.iter()) {
+            mesh.vertex_info.push(Vertex::new(pos, tex, [0.0, 0.0, 1.0]));
+        }
+        mesh.indices = vec![0, 1, 2, 0, 2, 3];
+        mesh
+    }
+
+    fn assert_close(a: Vector3<Float>, b: Vector3<Float>) {
+        assert!((a - b).magnitude() < 1e-5, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn flat_normals_split_every_corner_of_a_cube() {
+        let mut mesh = cube();
+        assert!(mesh.has_missing_normals());
+        mesh.compute_missing_normals(NormalMode::Flat);
+        assert!(!mesh.has_missing_normals());
+        // Four corners for each of the six faces.
+        assert_eq!(mesh.vertex_info.len(), 24);
+        let face_normal = vector(mesh.vertex_info[mesh.indices[0] as usize].norm);
+        assert_close(face_normal, Vector3::new(-1.0, 0.0, 0.0));
+        for &i in &mesh.indices[..6] {
+            assert_close(vector(mesh.vertex_info[i as usize].norm), face_normal);
+        }
+    }
+
+    #[test]
+    fn smooth_normals_keep_edges_sharper_than_the_crease_angle() {
+        let mut mesh = cube();
+        mesh.compute_missing_normals(NormalMode::Smooth { crease_angle: ::std::f32::consts::FRAC_PI_6 });
+        assert_eq!(mesh.vertex_info.len(), 24);
+        for vertex in &mesh.vertex_info {
+            let normal = vector(vertex.norm);
+            assert_close(normal, Vector3::new(normal.x.round(), normal.y.round(), normal.z.round()));
+        }
+    }
+
+    #[test]
+    fn smooth_normals_average_faces_within_the_crease_angle() {
+        let mut mesh = cube();
+        mesh.compute_missing_normals(NormalMode::Smooth { crease_angle: ::std::f32::consts::PI });
+        assert_eq!(mesh.vertex_info.len(), 8);
+        for vertex in &mesh.vertex_info {
+            // Every corner points away from the center of the cube.
+            let outward = (vector(vertex.pos) - Vector3::new(0.5, 0.5, 0.5)).normalize();
+            assert_close(vector(vertex.norm), outward);
         }
     }
+
+    #[test]
+    fn missing_normals_are_computed_without_touching_others() {
+        let mut mesh = cube();
+        let tilted = [0.6, 0.0, 0.8];
+        for &i in &mesh.indices[..3] {
+            mesh.vertex_info[i as usize].norm = tilted;
+        }
+        mesh.compute_missing_normals(NormalMode::Flat);
+        assert!(!mesh.has_missing_normals());
+        for &i in &mesh.indices[..3] {
+            assert_eq!(mesh.vertex_info[i as usize].norm, tilted);
+        }
+        // The second triangle of the face shares two of the corners with
+        // normals, and only its third corner gets the face normal.
+        let corner = mesh.indices[5] as usize;
+        assert_close(vector(mesh.vertex_info[corner].norm), Vector3::new(-1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn tangents_follow_the_texture_coordinates() {
+        let mesh = square([[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]);
+        for tangent in mesh.compute_tangents() {
+            assert_close(Vector3::new(tangent[0], tangent[1], tangent[2]), Vector3::new(1.0, 0.0, 0.0));
+            assert_eq!(tangent[3], 1.0);
+        }
+
+        // Mirroring the texture flips the tangent and the handedness.
+        let mesh = square([[1.0, 0.0], [0.0, 0.0], [0.0, 1.0], [1.0, 1.0]]);
+        for tangent in mesh.compute_tangents() {
+            assert_close(Vector3::new(tangent[0], tangent[1], tangent[2]), Vector3::new(-1.0, 0.0, 0.0));
+            assert_eq!(tangent[3], -1.0);
+        }
+    }
+
+    #[test]
+    fn tangents_without_texture_area_are_orthogonal_to_the_normal() {
+        let mesh = square([[0.0, 0.0]; 4]);
+        for tangent in mesh.compute_tangents() {
+            let tangent = Vector3::new(tangent[0], tangent[1], tangent[2]);
+            assert!((tangent.magnitude() - 1.0).abs() < 1e-5);
+            assert!(tangent.dot(Vector3::new(0.0, 0.0, 1.0)).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn welding_merges_close_vertices_and_drops_unused_ones() {
+        let mut mesh = square([[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]);
+        // Give the second triangle its own copies of the shared corners,
+        // slightly moved, and add a vertex that no triangle uses.
+        let (first, third) = (mesh.vertex_info[0], mesh.vertex_info[2]);
+        let mut nudged = first;
+        nudged.pos[0] += 1e-6;
+        mesh.vertex_info.push(nudged);
+        mesh.vertex_info.push(third);
+        mesh.vertex_info.push(Vertex::new([5.0, 5.0, 5.0], [0.0, 0.0], [0.0, 0.0, 1.0]));
+        mesh.indices = vec![0, 1, 2, 4, 5, 3];
+
+        assert_eq!(mesh.weld_vertices(1e-4), 3);
+        assert_eq!(mesh.vertex_info.len(), 4);
+        assert_eq!(mesh.indices, vec![0, 1, 2, 0, 2, 3]);
+
+        // Vertices with different texture coordinates are kept apart.
+        let mut mesh = square([[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]);
+        let mut seam = mesh.vertex_info[0];
+        seam.tex_coords = [1.0, 1.0];
+        mesh.vertex_info.push(seam);
+        mesh.indices = vec![0, 1, 2, 4, 2, 3];
+        assert_eq!(mesh.weld_vertices(1e-4), 0);
+    }
 }
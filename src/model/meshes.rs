@@ -1,24 +1,109 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::str;
+use std::path::Path;
+
 use model::mesh::Mesh;
 use loader::MeshLoader;
 use loader::obj_loader::ObjLoader;
+use loader::gltf_loader::GltfLoader;
+use assets::AssetManager;
+use console::Console;
+
+/// Name of the asset with the block model.
+pub const MESHES_ASSET: &str = "cube.obj";
+/// Names of glTF models that take the place of ``cube.obj`` when a resource
+/// directory has one of them. Only ``cube.obj`` is built in.
+const GLTF_MESHES_ASSETS: &[&str] = &["cube.gltf", "cube.glb"];
+/// Name of the block model in ``cube.obj``.
+const BLOCK_MESH_NAME: &str = "Cube";
+
+/// Loads the meshes from the content of ``cube.obj``, or from the file at
+/// ``path`` if it is not embedded. A glTF model is the block as a whole,
+/// with the transforms of its nodes applied.
+fn load_meshes(bytes: &[u8], path: Option<&Path>) -> Result<Vec<(String, Mesh)>, Box<dyn Error>> {
+    let meshes = match path {
+        Some(path) if path.extension().map(|ext| ext == "gltf" || ext == "glb").unwrap_or(false) => {
+            vec![(BLOCK_MESH_NAME.to_string(), GltfLoader::new().load_from_file(&path.to_string_lossy())?)]
+        },
+        Some(path) => {
+            // Models from other tools often use directives that are not
+            // supported, which the block can do without.
+            let mut loader = ObjLoader::new();
+            loader.set_lenient(true);
+            loader.load_named_from_file(&path.to_string_lossy())?
+        },
+        None => ObjLoader::new().load_named_from_str(str::from_utf8(bytes)?)?,
+    };
+    if !meshes.iter().any(|(name, _)| name == BLOCK_MESH_NAME) {
+        let file = path.map(|path| path.display().to_string()).unwrap_or_else(|| MESHES_ASSET.to_string());
+        return Err(Box::<dyn Error>::from(format!("{} has no ``{}`` object", file, BLOCK_MESH_NAME)));
+    }
+    Ok(meshes)
+}
+
+/// The glTF model in the resource directories, if there is one.
+fn gltf_asset(assets: &AssetManager) -> Option<&'static str> {
+    GLTF_MESHES_ASSETS.iter().cloned().find(|name| assets.find(name).is_some())
+}
+
+/// Whether the block model is among the ``changed`` assets.
+pub fn sources_changed(changed: &[String]) -> bool {
+    changed.iter().any(|name| name == MESHES_ASSET || GLTF_MESHES_ASSETS.contains(&name.as_str()))
+}
 
 pub struct Meshes {
-    loader: Box<MeshLoader>,
-    pub block: Mesh,
+    meshes: HashMap<String, Mesh>,
 }
 
 impl Meshes {
-    pub fn load() -> Meshes {
-        let mut loader = Box::new(ObjLoader::new());
-        let block = loader.load_from_str(include_str!("../../cube.obj")).unwrap();
-        Meshes {
-            loader: loader,
-            block: block,
+    /// Loads the meshes from ``assets``, preferring a glTF model over
+    /// ``cube.obj``, and watches the glTF models for changes. Material
+    /// libraries and buffers of an overriding model are looked up next to
+    /// it. If the override fails to load, the built-in meshes are used and
+    /// the problem is reported on ``console``.
+    pub fn load(assets: &mut AssetManager, console: &mut Console) -> Meshes {
+        for name in GLTF_MESHES_ASSETS {
+            assets.watch(name);
         }
+        let meshes = match gltf_asset(assets) {
+            Some(name) => assets.build(name, load_meshes).or_else(|e| {
+                console.print(&format!("Failed to load {}, using {} instead: {}", name, MESHES_ASSET, e));
+                assets.load_with(MESHES_ASSET, load_meshes, console)
+            }),
+            None => assets.load_with(MESHES_ASSET, load_meshes, console),
+        };
+        Meshes { meshes: meshes.unwrap().into_iter().collect() }
+    }
+
+    /// Loads the meshes from ``assets`` again. If that fails, the current
+    /// meshes are kept.
+    pub fn reload(&mut self, assets: &AssetManager) -> Result<(), Box<dyn Error>> {
+        let meshes = assets.build(gltf_asset(assets).unwrap_or(MESHES_ASSET), load_meshes)?;
+        self.meshes = meshes.into_iter().collect();
+        Ok(())
+    }
+
+    pub fn block(&self) -> &Mesh {
+        &self.meshes[BLOCK_MESH_NAME]
     }
 }
 
-unsafe impl Sync for Meshes {
-    //Implement Sync so the meshes can be shared between threads. This is safe,
-    //since the meshes class is just a read-only static storage of meshes.
+#[cfg(test)]
+mod tests {
+    use assets;
+    use super::{load_meshes, MESHES_ASSET, BLOCK_MESH_NAME};
+
+    #[test]
+    fn the_embedded_model_has_a_block_mesh() {
+        let bytes = assets::embedded(MESHES_ASSET).unwrap();
+        let meshes = load_meshes(bytes, None).unwrap();
+        assert!(meshes.iter().any(|(name, mesh)| name == BLOCK_MESH_NAME && !mesh.indices.is_empty()));
+    }
+
+    #[test]
+    fn models_without_a_block_mesh_are_rejected() {
+        let obj = b"v 0 0 0\nv 1 0 0\nv 0 1 0\no Slab\nf 1 2 3\n";
+        assert!(load_meshes(obj, None).is_err());
+    }
 }
@@ -0,0 +1,51 @@
+use cgmath::Point3;
+
+use world::region::Region;
+
+/// A box of blocks spanned by two corners, which are set one at a time with
+/// the selection tool.
+#[derive(Debug)]
+pub struct Selection {
+    corners: [Option<Point3<usize>>; 2],
+    tool_active: bool,
+}
+
+impl Selection {
+    pub fn new() -> Selection {
+        Selection { corners: [None, None], tool_active: false }
+    }
+
+    /// Whether clicks set the corners of the selection instead of placing
+    /// and breaking blocks.
+    pub fn tool_active(&self) -> bool {
+        self.tool_active
+    }
+
+    pub fn toggle_tool(&mut self) {
+        self.tool_active = !self.tool_active;
+    }
+
+    /// Sets the first (``index`` 0) or second (``index`` 1) corner.
+    pub fn set_corner(&mut self, index: usize, pos: Point3<usize>) {
+        self.corners[index] = Some(pos);
+    }
+
+    /// Sets both corners at once.
+    pub fn set(&mut self, region: &Region) {
+        self.corners = [Some(region.min), Some(region.max)];
+    }
+
+    pub fn clear(&mut self) {
+        self.corners = [None, None];
+    }
+
+    /// The selected region. While only one corner is set, the selection is
+    /// the single block at that corner.
+    pub fn region(&self) -> Option<Region> {
+        match (self.corners[0], self.corners[1]) {
+            (Some(a), Some(b)) => Some(Region::new(&a, &b)),
+            (Some(a), None) | (None, Some(a)) => Some(Region::new(&a, &a)),
+            (None, None) => None,
+        }
+    }
+}
@@ -1 +1 @@
-pub const REACH_DISTANCE: usize = 5;
+pub const SETTINGS_FILE: &str = "settings.toml";
@@ -0,0 +1,47 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Number of frames the frame time is averaged over.
+const SAMPLE_COUNT: usize = 60;
+
+/// Keeps track of the time taken by recent frames.
+pub struct FrameTimer {
+    last_frame: Instant,
+    frame_times: VecDeque<Duration>,
+}
+
+impl FrameTimer {
+    pub fn new() -> FrameTimer {
+        FrameTimer {
+            last_frame: Instant::now(),
+            frame_times: VecDeque::with_capacity(SAMPLE_COUNT),
+        }
+    }
+
+    /// Marks the start of a new frame.
+    pub fn tick(&mut self) {
+        let now = Instant::now();
+        if self.frame_times.len() == SAMPLE_COUNT {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(now - self.last_frame);
+        self.last_frame = now;
+    }
+
+    /// The average frame time in milliseconds.
+    pub fn frame_time_ms(&self) -> f64 {
+        if self.frame_times.is_empty() {
+            return 0.0;
+        }
+
+        let total = self.frame_times.iter().fold(0.0, |total, d| {
+            total + d.as_secs() as f64 * 1000.0 + d.subsec_nanos() as f64 / 1_000_000.0
+        });
+        total / self.frame_times.len() as f64
+    }
+
+    pub fn fps(&self) -> f64 {
+        let frame_time = self.frame_time_ms();
+        if frame_time > 0.0 { 1000.0 / frame_time } else { 0.0 }
+    }
+}
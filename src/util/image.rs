@@ -0,0 +1,82 @@
+use std::io::{Cursor, Write};
+use std::error::Error;
+
+use png::{self, HasParameters};
+
+/// An 8-bit RGBA image with its rows from top to bottom.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    /// Creates a transparent image.
+    pub fn new(width: u32, height: u32) -> Image {
+        Image { width, height, pixels: vec![0; (width * height * 4) as usize] }
+    }
+
+    /// Decodes an 8-bit RGB or RGBA png image.
+    pub fn decode_png(png_bytes: &[u8]) -> Result<Image, Box<dyn Error>> {
+        let decoder = png::Decoder::new(Cursor::new(png_bytes));
+        let (info, mut reader) = decoder.read_info()?;
+        if info.bit_depth != png::BitDepth::Eight {
+            return Err(Box::<dyn Error>::from("Only 8-bit images are supported"));
+        }
+        let mut buf = vec![0; info.buffer_size()];
+        reader.next_frame(&mut buf)?;
+
+        let pixels = match info.color_type {
+            png::ColorType::RGBA => buf,
+            png::ColorType::RGB => buf.chunks(3).flat_map(|rgb| vec![rgb[0], rgb[1], rgb[2], 255]).collect(),
+            _ => return Err(Box::<dyn Error>::from("Only RGB and RGBA images are supported")),
+        };
+        Ok(Image { width: info.width, height: info.height, pixels })
+    }
+
+    pub fn write_png<W: Write>(&self, writer: W) -> Result<(), Box<dyn Error>> {
+        let mut encoder = png::Encoder::new(writer, self.width, self.height);
+        encoder.set(png::ColorType::RGBA).set(png::BitDepth::Eight);
+        encoder.write_header()?.write_image_data(&self.pixels)?;
+        Ok(())
+    }
+
+    fn offset(&self, x: u32, y: u32) -> usize {
+        ((y * self.width + x) * 4) as usize
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = self.offset(x, y);
+        [self.pixels[i], self.pixels[i + 1], self.pixels[i + 2], self.pixels[i + 3]]
+    }
+
+    pub fn set_pixel(&mut self, x: u32, y: u32, pixel: [u8; 4]) {
+        let i = self.offset(x, y);
+        self.pixels[i..i + 4].copy_from_slice(&pixel);
+    }
+
+    /// The ``width`` by ``height`` part of the image with its top left corner
+    /// at ``x``, ``y``.
+    pub fn sub_image(&self, x: u32, y: u32, width: u32, height: u32) -> Image {
+        let mut image = Image::new(width, height);
+        for dy in 0..height {
+            for dx in 0..width {
+                image.set_pixel(dx, dy, self.pixel(x + dx, y + dy));
+            }
+        }
+        image
+    }
+
+    /// The image scaled to ``width`` by ``height`` with nearest neighbor
+    /// sampling, which keeps pixel art sharp.
+    pub fn resized(&self, width: u32, height: u32) -> Image {
+        let mut image = Image::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                image.set_pixel(x, y, self.pixel(x * self.width / width, y * self.height / height));
+            }
+        }
+        image
+    }
+}
@@ -0,0 +1,20 @@
+/// Generates a pair of functions that map the unit variants of an enum to
+/// their names and back.
+macro_rules! name_mapping {
+    ($ty:ident, $from_name:ident, $to_name:ident, { $($variant:ident => $name:tt),* $(,)* }) => {
+        pub fn $from_name(name: &str) -> Option<$ty> {
+            match name {
+                $($name => Some($ty::$variant),)*
+                _ => None,
+            }
+        }
+
+        pub fn $to_name(value: $ty) -> Option<&'static str> {
+            match value {
+                $($ty::$variant => Some($name),)*
+                #[allow(unreachable_patterns)]
+                _ => None,
+            }
+        }
+    }
+}
@@ -1,4 +1,7 @@
+#[macro_use]
+pub mod macros;
 pub mod math;
 pub mod types;
-pub mod macros;
 pub mod constants;
+pub mod frame_timer;
+pub mod image;
@@ -0,0 +1,224 @@
+use std::collections::VecDeque;
+
+use cgmath::Point3;
+
+use model::block::BlockType;
+
+/// The change of a single block.
+#[derive(Debug, Copy, Clone)]
+pub struct BlockChange {
+    pub pos: Point3<usize>,
+    pub before: BlockType,
+    pub after: BlockType,
+}
+
+/// The block changes made by one edit, which can be undone and redone as a
+/// whole.
+#[derive(Debug, Clone)]
+pub struct ChangeSet {
+    changes: Vec<BlockChange>,
+}
+
+impl ChangeSet {
+    pub fn new() -> ChangeSet {
+        ChangeSet { changes: Vec::new() }
+    }
+
+    pub fn push(&mut self, pos: Point3<usize>, before: BlockType, after: BlockType) {
+        self.changes.push(BlockChange { pos, before, after });
+    }
+
+    pub fn changes(&self) -> &[BlockChange] {
+        &self.changes
+    }
+
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// The change set that reverts this one.
+    pub fn inverted(&self) -> ChangeSet {
+        ChangeSet {
+            changes: self.changes.iter().rev()
+                .map(|c| BlockChange { pos: c.pos, before: c.after, after: c.before })
+                .collect(),
+        }
+    }
+}
+
+/// A bounded list of change sets that can be undone, and of the undone
+/// change sets that can be redone.
+#[derive(Debug)]
+pub struct History {
+    undo: VecDeque<ChangeSet>,
+    redo: Vec<ChangeSet>,
+    capacity: usize,
+}
+
+impl History {
+    /// Creates a history that remembers at most ``capacity`` edits.
+    pub fn new(capacity: usize) -> History {
+        History { undo: VecDeque::new(), redo: Vec::new(), capacity }
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.undo.len() > capacity {
+            self.undo.pop_front();
+        }
+        // The edits that would be redone last are at the front.
+        if self.redo.len() > capacity {
+            let excess = self.redo.len() - capacity;
+            self.redo.drain(..excess);
+        }
+    }
+
+    /// Records a new edit. This discards the edits that were undone, as they
+    /// can no longer be redone, and the oldest edit when the history is full.
+    pub fn record(&mut self, changes: ChangeSet) {
+        if changes.is_empty() || self.capacity == 0 {
+            return;
+        }
+
+        self.redo.clear();
+        if self.undo.len() == self.capacity {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(changes);
+    }
+
+    /// Takes the most recent edit and returns the change set that reverts it.
+    pub fn undo(&mut self) -> Option<ChangeSet> {
+        self.undo.pop_back().map(|changes| {
+            let inverted = changes.inverted();
+            self.redo.push(changes);
+            inverted
+        })
+    }
+
+    /// Takes the most recently undone edit and returns the change set that
+    /// applies it again.
+    pub fn redo(&mut self) -> Option<ChangeSet> {
+        match self.redo.pop() {
+            Some(changes) => {
+                if self.undo.len() == self.capacity {
+                    self.undo.pop_front();
+                }
+                self.undo.push_back(changes.clone());
+                Some(changes)
+            },
+            None => None,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    #[cfg(test)]
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::Point3;
+
+    use model::block::BlockType;
+    use super::{ChangeSet, History};
+
+    /// An edit that sets the block at ``x``, 0, 0 from air to ``block``.
+    fn edit(x: usize, block: usize) -> ChangeSet {
+        let mut changes = ChangeSet::new();
+        changes.push(Point3::new(x, 0, 0), BlockType::Void(0), BlockType::Solid(block));
+        changes
+    }
+
+    #[test]
+    fn undo_returns_the_inverted_edit_and_redo_the_edit() {
+        let mut history = History::new(10);
+        history.record(edit(1, 2));
+        let undo = history.undo().unwrap();
+        assert_eq!(undo.changes()[0].before, BlockType::Solid(2));
+        assert_eq!(undo.changes()[0].after, BlockType::Void(0));
+        let redo = history.redo().unwrap();
+        assert_eq!(redo.changes()[0].after, BlockType::Solid(2));
+        assert!(history.redo().is_none());
+        assert_eq!(history.undo_len(), 1);
+    }
+
+    #[test]
+    fn inverting_reverses_the_order_of_changes() {
+        let mut changes = edit(1, 2);
+        changes.push(Point3::new(1, 0, 0), BlockType::Solid(2), BlockType::Solid(3));
+        let inverted = changes.inverted();
+        assert_eq!(inverted.changes()[0].before, BlockType::Solid(3));
+        assert_eq!(inverted.changes()[1].after, BlockType::Void(0));
+    }
+
+    #[test]
+    fn forgets_the_oldest_edits_beyond_capacity() {
+        let mut history = History::new(2);
+        for i in 0..3 {
+            history.record(edit(i, i));
+        }
+        assert_eq!(history.undo_len(), 2);
+        assert_eq!(history.undo().unwrap().changes()[0].pos, Point3::new(2, 0, 0));
+        assert_eq!(history.undo().unwrap().changes()[0].pos, Point3::new(1, 0, 0));
+        assert!(history.undo().is_none());
+
+        history.set_capacity(1);
+        assert_eq!(history.redo_len(), 1);
+        assert_eq!(history.redo().unwrap().changes()[0].pos, Point3::new(1, 0, 0));
+        assert!(history.redo().is_none());
+        assert_eq!(history.undo_len(), 1);
+    }
+
+    #[test]
+    fn redo_keeps_the_capacity() {
+        let mut history = History::new(2);
+        for i in 0..2 {
+            history.record(edit(i, i));
+        }
+        history.undo();
+        history.undo();
+        history.set_capacity(2);
+        history.redo();
+        history.redo();
+        assert_eq!(history.undo_len(), 2);
+        history.set_capacity(1);
+        assert_eq!(history.undo_len(), 1);
+        assert_eq!(history.undo().unwrap().changes()[0].pos, Point3::new(1, 0, 0));
+    }
+
+    #[test]
+    fn recording_discards_undone_edits() {
+        let mut history = History::new(10);
+        history.record(edit(1, 1));
+        history.record(edit(2, 2));
+        history.undo();
+        assert_eq!(history.redo_len(), 1);
+        history.record(edit(3, 3));
+        assert_eq!(history.redo_len(), 0);
+        assert!(history.redo().is_none());
+        assert_eq!(history.undo().unwrap().changes()[0].pos, Point3::new(3, 0, 0));
+        assert_eq!(history.undo().unwrap().changes()[0].pos, Point3::new(1, 0, 0));
+    }
+
+    #[test]
+    fn ignores_empty_edits_and_zero_capacity() {
+        let mut history = History::new(10);
+        history.record(ChangeSet::new());
+        assert_eq!(history.undo_len(), 0);
+
+        let mut history = History::new(0);
+        history.record(edit(1, 1));
+        assert!(history.undo().is_none());
+    }
+}
@@ -0,0 +1,77 @@
+use cgmath::{Point3, Vector3};
+
+use util::types::Float;
+use gl::vertex::Vertex;
+use model::mesh::Mesh;
+use model::block::BlockType;
+use model::atlas::{Face, UvTable};
+use world::structure::Structure;
+
+/// Outward normal and texture up direction of each face of a block. Seen
+/// from outside, the texture is upright on the sides and faces north on the
+/// top and bottom.
+const FACES: [([i32; 3], [Float; 3]); 6] = [
+    ([1, 0, 0], [0.0, 1.0, 0.0]),
+    ([-1, 0, 0], [0.0, 1.0, 0.0]),
+    ([0, 1, 0], [0.0, 0.0, -1.0]),
+    ([0, -1, 0], [0.0, 0.0, 1.0]),
+    ([0, 0, 1], [0.0, 1.0, 0.0]),
+    ([0, 0, -1], [0.0, 1.0, 0.0]),
+];
+
+fn is_solid(structure: &Structure, pos: &Point3<usize>, offset: &[i32; 3]) -> bool {
+    let size = structure.size();
+    let mut neighbor = *pos;
+    for i in 0..3 {
+        let coord = pos[i] as i64 + offset[i] as i64;
+        if coord < 0 || coord >= size[i] as i64 {
+            return false;
+        }
+        neighbor[i] = coord as usize;
+    }
+    match structure.get(&neighbor) {
+        BlockType::Solid(_) => true,
+        BlockType::Void(_) => false,
+    }
+}
+
+/// Builds a mesh of the faces of the blocks in ``structure`` that are not
+/// covered by a neighboring block. Blocks outside the structure count as
+/// air. Every block is a unit cube with its minimum corner at its position,
+/// and each face is mapped to its tile in the texture atlas as given by
+/// ``uvs``.
+pub fn build_mesh(structure: &Structure, uvs: &UvTable) -> Mesh {
+    let mut mesh = Mesh::new();
+    for pos in structure.region_at(&Point3::new(0, 0, 0)).iter() {
+        let block_type = structure.get(&pos);
+        if block_type.info().is_none() {
+            continue;
+        }
+        let center = Vector3::new(pos.x as Float, pos.y as Float, pos.z as Float) + Vector3::from([0.5; 3]);
+
+        for &(ref normal, up) in FACES.iter() {
+            if is_solid(structure, &pos, normal) {
+                continue;
+            }
+            let (uv_min, uv_max) = uvs.rect(block_type, Face::from_normal(normal));
+
+            let normal = Vector3::new(normal[0] as Float, normal[1] as Float, normal[2] as Float);
+            let up = Vector3::from(up);
+            let right = up.cross(normal);
+            let face_center = center + normal * 0.5;
+
+            let first = mesh.vertex_info.len() as u32;
+            // Counter-clockwise when seen from outside.
+            for &(s, t) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)].iter() {
+                let vert = face_center + right * (s - 0.5) + up * (t - 0.5);
+                let tex_coords = [
+                    uv_min[0] + (uv_max[0] - uv_min[0]) * s,
+                    uv_min[1] + (uv_max[1] - uv_min[1]) * t,
+                ];
+                mesh.vertex_info.push(Vertex::new(vert.into(), tex_coords, normal.into()));
+            }
+            mesh.indices.extend_from_slice(&[first, first + 1, first + 2, first, first + 2, first + 3]);
+        }
+    }
+    mesh
+}
@@ -1 +1,8 @@
+// The module is named after the ``World`` type it defines.
+#[allow(clippy::module_inception)]
 pub mod world;
+pub mod region;
+pub mod structure;
+pub mod history;
+pub mod schematic;
+pub mod mesher;
@@ -0,0 +1,89 @@
+use std::cmp::{min, max};
+
+use cgmath::{Point3, Vector3};
+
+/// An axis-aligned box of block positions. Both corners are inclusive.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Region {
+    pub min: Point3<usize>,
+    pub max: Point3<usize>,
+}
+
+impl Region {
+    /// Creates the region spanned by two opposite corners, given in any order.
+    pub fn new(a: &Point3<usize>, b: &Point3<usize>) -> Region {
+        Region {
+            min: Point3::new(min(a.x, b.x), min(a.y, b.y), min(a.z, b.z)),
+            max: Point3::new(max(a.x, b.x), max(a.y, b.y), max(a.z, b.z)),
+        }
+    }
+
+    /// Number of blocks along each axis, saturating at ``usize::MAX``.
+    pub fn size(&self) -> Vector3<usize> {
+        let size = self.max - self.min;
+        Vector3::new(size.x.saturating_add(1), size.y.saturating_add(1), size.z.saturating_add(1))
+    }
+
+    /// Number of blocks in the region, saturating at ``usize::MAX``.
+    pub fn volume(&self) -> usize {
+        let size = self.size();
+        size.x.saturating_mul(size.y).saturating_mul(size.z)
+    }
+
+    /// Whether ``pos`` lies on one of the six faces of the region.
+    pub fn is_on_boundary(&self, pos: &Point3<usize>) -> bool {
+        (0..3).any(|i| pos[i] == self.min[i] || pos[i] == self.max[i])
+    }
+
+    /// Whether ``pos`` lies on one of the four vertical faces of the region.
+    pub fn is_on_walls(&self, pos: &Point3<usize>) -> bool {
+        [0, 2].iter().any(|&i| pos[i] == self.min[i] || pos[i] == self.max[i])
+    }
+
+    /// The part of the region that lies within ``size`` blocks from the
+    /// origin along every axis, if any.
+    pub fn clip(&self, size: &Vector3<usize>) -> Option<Region> {
+        if (0..3).any(|i| size[i] == 0 || self.min[i] >= size[i]) {
+            return None;
+        }
+
+        let max = Point3::new(min(self.max.x, size.x - 1), min(self.max.y, size.y - 1), min(self.max.z, size.z - 1));
+        Some(Region { min: self.min, max })
+    }
+
+    /// Iterates over all positions in the region, in x, y, z order.
+    pub fn iter(&self) -> RegionIter {
+        RegionIter { region: *self, next: Some(self.min) }
+    }
+}
+
+pub struct RegionIter {
+    region: Region,
+    next: Option<Point3<usize>>,
+}
+
+impl Iterator for RegionIter {
+    type Item = Point3<usize>;
+
+    fn next(&mut self) -> Option<Point3<usize>> {
+        let current = self.next;
+        if let Some(mut pos) = current {
+            self.next = if pos.z < self.region.max.z {
+                pos.z += 1;
+                Some(pos)
+            } else if pos.y < self.region.max.y {
+                pos.z = self.region.min.z;
+                pos.y += 1;
+                Some(pos)
+            } else if pos.x < self.region.max.x {
+                pos.z = self.region.min.z;
+                pos.y = self.region.min.y;
+                pos.x += 1;
+                Some(pos)
+            } else {
+                None
+            };
+        }
+        current
+    }
+}
@@ -0,0 +1,296 @@
+use std::io::{self, Read, Write, BufReader, BufWriter};
+use std::fs::File;
+use std::path::Path;
+use std::error::Error;
+use std::collections::BTreeMap;
+
+use cgmath::{Point3, Vector3};
+
+use model::block::BlockType;
+use world::structure::Structure;
+
+/// File extension of schematic files.
+pub const EXTENSION: &str = "blks";
+
+const MAGIC: &[u8; 4] = b"BLKS";
+const VERSION: u8 = 1;
+
+/// Largest number of blocks a schematic can hold, so a corrupt size cannot
+/// make loading allocate without bounds.
+pub const MAX_BLOCKS: usize = 1 << 24;
+
+/// A structure together with free-form metadata such as its author, as
+/// stored in a schematic file.
+///
+/// All numbers in the file are little endian. The file starts with the magic
+/// bytes ``BLKS`` and a version byte, followed by
+///
+/// * the size of the structure as three ``u16``,
+/// * the palette: a ``u16`` count and the block names, each as a string,
+/// * the metadata: a ``u16`` count and pairs of key and value strings,
+/// * the block data: a ``u8`` number of bits per block and the palette
+///   indices of all blocks in x, y, z order, packed starting at the lowest
+///   bit of each byte.
+///
+/// Strings are stored as a ``u16`` length and UTF-8 bytes. Blocks are stored
+/// by name, so schematics stay valid when block types are added.
+#[derive(Debug, Clone)]
+pub struct Schematic {
+    pub structure: Structure,
+    pub metadata: BTreeMap<String, String>,
+}
+
+fn write_u16<W: Write>(writer: &mut W, value: usize) -> Result<(), Box<dyn Error>> {
+    if value > u16::MAX as usize {
+        return Err(Box::<dyn Error>::from(format!("{} does not fit in a schematic", value)));
+    }
+    writer.write_all(&[value as u8, (value >> 8) as u8])?;
+    Ok(())
+}
+
+fn write_string<W: Write>(writer: &mut W, s: &str) -> Result<(), Box<dyn Error>> {
+    write_u16(writer, s.len())?;
+    writer.write_all(s.as_bytes())?;
+    Ok(())
+}
+
+fn read_u8<R: Read>(reader: &mut R) -> io::Result<u8> {
+    let mut buf = [0; 1];
+    reader.read_exact(&mut buf)?;
+    Ok(buf[0])
+}
+
+fn read_u16<R: Read>(reader: &mut R) -> io::Result<usize> {
+    let mut buf = [0; 2];
+    reader.read_exact(&mut buf)?;
+    Ok(buf[0] as usize | (buf[1] as usize) << 8)
+}
+
+fn read_string<R: Read>(reader: &mut R) -> Result<String, Box<dyn Error>> {
+    let mut buf = vec![0; read_u16(reader)?];
+    reader.read_exact(&mut buf)?;
+    Ok(String::from_utf8(buf)?)
+}
+
+/// Number of bits needed to store the indices into a palette of ``len``
+/// entries.
+fn bits_for(len: usize) -> usize {
+    let mut bits = 1;
+    while (1 << bits) < len {
+        bits += 1;
+    }
+    bits
+}
+
+impl Schematic {
+    pub fn new(structure: Structure) -> Schematic {
+        Schematic { structure, metadata: BTreeMap::new() }
+    }
+
+    pub fn load<P>(path: P) -> Result<Schematic, Box<dyn Error>>
+        where P: AsRef<Path>
+    {
+        let file = File::open(path)?;
+        Schematic::read(&mut BufReader::new(file))
+    }
+
+    pub fn save<P>(&self, path: P) -> Result<(), Box<dyn Error>>
+        where P: AsRef<Path>
+    {
+        let file = File::create(path)?;
+        let mut writer = BufWriter::new(file);
+        self.write(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<(), Box<dyn Error>> {
+        let size = self.structure.size();
+        let blocks = self.structure.blocks();
+        if blocks.len() > MAX_BLOCKS {
+            return Err(Box::<dyn Error>::from(format!("Schematics can hold at most {} blocks", MAX_BLOCKS)));
+        }
+
+        let mut palette: Vec<&'static str> = Vec::new();
+        let indices = blocks.iter().map(|block_type| {
+            let name = block_type.name();
+            match palette.iter().position(|&n| n == name) {
+                Some(i) => i,
+                None => {
+                    palette.push(name);
+                    palette.len() - 1
+                },
+            }
+        }).collect::<Vec<usize>>();
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION])?;
+        for i in 0..3 {
+            write_u16(writer, size[i])?;
+        }
+
+        write_u16(writer, palette.len())?;
+        for name in &palette {
+            write_string(writer, name)?;
+        }
+
+        write_u16(writer, self.metadata.len())?;
+        for (key, value) in &self.metadata {
+            write_string(writer, key)?;
+            write_string(writer, value)?;
+        }
+
+        let bits = bits_for(palette.len());
+        let mut data = vec![0u8; (indices.len() * bits).div_ceil(8)];
+        for (i, &index) in indices.iter().enumerate() {
+            for bit in 0..bits {
+                if index & (1 << bit) != 0 {
+                    let bit_pos = i * bits + bit;
+                    data[bit_pos / 8] |= 1 << (bit_pos % 8);
+                }
+            }
+        }
+        writer.write_all(&[bits as u8])?;
+        writer.write_all(&data)?;
+        Ok(())
+    }
+
+    pub fn read<R: Read>(reader: &mut R) -> Result<Schematic, Box<dyn Error>> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(Box::<dyn Error>::from("Not a schematic file"));
+        }
+        let version = read_u8(reader)?;
+        if version != VERSION {
+            return Err(Box::<dyn Error>::from(format!("Unsupported schematic version {}", version)));
+        }
+
+        let size = Vector3::new(read_u16(reader)?, read_u16(reader)?, read_u16(reader)?);
+        if size.x == 0 || size.y == 0 || size.z == 0 {
+            return Err(Box::<dyn Error>::from("The schematic is empty"));
+        }
+        let count = size.x * size.y * size.z;
+        if count > MAX_BLOCKS {
+            return Err(Box::<dyn Error>::from(format!("The schematic has more than {} blocks", MAX_BLOCKS)));
+        }
+
+        let mut palette = Vec::new();
+        for _ in 0..read_u16(reader)? {
+            let name = read_string(reader)?;
+            match BlockType::from_name(&name) {
+                Some(block_type) => palette.push(block_type),
+                None => return Err(Box::<dyn Error>::from(format!("Unknown block type ``{}``", name))),
+            }
+        }
+
+        let mut metadata = BTreeMap::new();
+        for _ in 0..read_u16(reader)? {
+            let key = read_string(reader)?;
+            let value = read_string(reader)?;
+            metadata.insert(key, value);
+        }
+
+        let bits = read_u8(reader)? as usize;
+        if bits == 0 || bits > 16 {
+            return Err(Box::<dyn Error>::from(format!("Invalid number of bits per block: {}", bits)));
+        }
+        // Reading only what is there keeps a truncated file from allocating
+        // the full size up front.
+        let expected = (count * bits).div_ceil(8);
+        let mut data = Vec::new();
+        reader.take(expected as u64).read_to_end(&mut data)?;
+        if data.len() != expected {
+            return Err(Box::<dyn Error>::from(format!("The block data is truncated: expected {} bytes, found {}", expected, data.len())));
+        }
+
+        let mut structure = Structure::new(size, BlockType::Void(0));
+        let region = structure.region_at(&Point3::new(0, 0, 0));
+        for (i, pos) in region.iter().enumerate() {
+            let mut index = 0;
+            for bit in 0..bits {
+                let bit_pos = i * bits + bit;
+                if data[bit_pos / 8] & (1 << (bit_pos % 8)) != 0 {
+                    index |= 1 << bit;
+                }
+            }
+            match palette.get(index) {
+                Some(&block_type) => structure.set(&pos, block_type),
+                None => return Err(Box::<dyn Error>::from(format!("Invalid palette index {}", index))),
+            }
+        }
+
+        Ok(Schematic { structure, metadata })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use cgmath::{Point3, Vector3};
+
+    use model::block::{BlockType, BLOCK_INFOS};
+    use world::structure::Structure;
+    use super::{bits_for, Schematic, MAX_BLOCKS};
+
+    fn round_trip(schematic: &Schematic) -> Schematic {
+        let mut bytes = Vec::new();
+        schematic.write(&mut bytes).unwrap();
+        Schematic::read(&mut Cursor::new(bytes)).unwrap()
+    }
+
+    #[test]
+    fn bits_for_fits_all_palette_indices() {
+        assert_eq!(bits_for(1), 1);
+        assert_eq!(bits_for(2), 1);
+        assert_eq!(bits_for(3), 2);
+        assert_eq!(bits_for(4), 2);
+        assert_eq!(bits_for(5), 3);
+        assert_eq!(bits_for(256), 8);
+        assert_eq!(bits_for(257), 9);
+    }
+
+    #[test]
+    fn round_trips_blocks_and_metadata() {
+        let mut structure = Structure::new(Vector3::new(3, 2, 5), BlockType::Void(0));
+        // Every block type, so the indices take several bits and cross byte
+        // boundaries.
+        for (i, pos) in structure.region_at(&Point3::new(0, 0, 0)).iter().enumerate() {
+            let block_type = match i % (BLOCK_INFOS.len() + 1) {
+                0 => BlockType::Void(0),
+                n => BlockType::Solid(n - 1),
+            };
+            structure.set(&pos, block_type);
+        }
+        let mut schematic = Schematic::new(structure);
+        schematic.metadata.insert("author".to_string(), "Somebody".to_string());
+
+        let read = round_trip(&schematic);
+        assert_eq!(read.structure, schematic.structure);
+        assert_eq!(read.metadata, schematic.metadata);
+    }
+
+    #[test]
+    fn round_trips_a_single_block_type() {
+        let schematic = Schematic::new(Structure::new(Vector3::new(1, 1, 1), BlockType::Solid(3)));
+        assert_eq!(round_trip(&schematic).structure, schematic.structure);
+    }
+
+    #[test]
+    fn rejects_truncated_block_data() {
+        let schematic = Schematic::new(Structure::new(Vector3::new(4, 4, 4), BlockType::Solid(0)));
+        let mut bytes = Vec::new();
+        schematic.write(&mut bytes).unwrap();
+        bytes.pop();
+        assert!(Schematic::read(&mut Cursor::new(bytes)).is_err());
+    }
+
+    #[test]
+    fn rejects_oversized_schematics_before_allocating() {
+        let mut bytes = b"BLKS\x01".to_vec();
+        bytes.extend_from_slice(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff]);
+        let error = Schematic::read(&mut Cursor::new(bytes)).unwrap_err();
+        assert!(error.to_string().contains(&MAX_BLOCKS.to_string()), "{}", error);
+    }
+}
@@ -0,0 +1,166 @@
+use cgmath::{Point3, Vector3};
+
+use model::block::BlockType;
+use world::world::World;
+use world::region::Region;
+
+/// A box of blocks that was copied out of a world and can be transformed and
+/// pasted back into one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Structure {
+    size: Vector3<usize>,
+    /// Block types in x, y, z order.
+    blocks: Vec<BlockType>,
+}
+
+impl Structure {
+    /// Creates a structure of the given size filled with ``block_type``.
+    pub fn new(size: Vector3<usize>, block_type: BlockType) -> Structure {
+        Structure { size, blocks: vec![block_type; size.x * size.y * size.z] }
+    }
+
+    /// Copies the part of ``region`` that lies within ``world`` out of it, or
+    /// returns ``None`` if no part of it does.
+    pub fn from_world(world: &World, region: &Region) -> Option<Structure> {
+        region.clip(&world.size()).map(|region| Structure {
+            size: region.size(),
+            blocks: region.iter().map(|pos| world.block_type(&pos).unwrap_or(BlockType::Void(0))).collect(),
+        })
+    }
+
+    /// Number of blocks along each axis.
+    pub fn size(&self) -> Vector3<usize> {
+        self.size
+    }
+
+    pub fn blocks(&self) -> &[BlockType] {
+        &self.blocks
+    }
+
+    fn index(&self, pos: &Point3<usize>) -> usize {
+        (pos.x * self.size.y + pos.y) * self.size.z + pos.z
+    }
+
+    pub fn get(&self, pos: &Point3<usize>) -> BlockType {
+        self.blocks[self.index(pos)]
+    }
+
+    pub fn set(&mut self, pos: &Point3<usize>, block_type: BlockType) {
+        let i = self.index(pos);
+        self.blocks[i] = block_type;
+    }
+
+    /// The region the structure covers when pasted with its minimum corner
+    /// at ``origin``.
+    pub fn region_at(&self, origin: &Point3<usize>) -> Region {
+        let max = Point3::new(
+            origin.x + self.size.x.saturating_sub(1),
+            origin.y + self.size.y.saturating_sub(1),
+            origin.z + self.size.z.saturating_sub(1));
+        Region::new(origin, &max)
+    }
+
+    /// Builds a new structure of ``size`` where the block at every position
+    /// is taken from the position ``source`` maps it to in this structure.
+    fn remap<F>(&self, size: Vector3<usize>, source: F) -> Structure
+        where F: Fn(&Point3<usize>) -> Point3<usize>
+    {
+        let mut result = Structure::new(size, BlockType::Void(0));
+        for pos in result.region_at(&Point3::new(0, 0, 0)).iter() {
+            let block_type = self.get(&source(&pos));
+            result.set(&pos, block_type);
+        }
+        result
+    }
+
+    /// The structure rotated clockwise around the Y axis, when seen from
+    /// above, by ``quarter_turns`` times 90 degrees.
+    pub fn rotated_y(&self, quarter_turns: usize) -> Structure {
+        let mut result = self.clone();
+        for _ in 0..quarter_turns % 4 {
+            let size = result.size;
+            result = result.remap(Vector3::new(size.z, size.y, size.x), |pos| {
+                Point3::new(pos.z, pos.y, size.z - 1 - pos.x)
+            });
+        }
+        result
+    }
+
+    /// The structure mirrored along ``axis``.
+    pub fn mirrored(&self, axis: usize) -> Structure {
+        let size = self.size;
+        self.remap(size, |pos| {
+            let mut source = *pos;
+            source[axis] = size[axis] - 1 - pos[axis];
+            source
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cgmath::{Point3, Vector3};
+
+    use model::block::BlockType;
+    use world::region::Region;
+    use world::world::World;
+    use super::Structure;
+
+    /// A structure where every block has a different type.
+    fn numbered(size: Vector3<usize>) -> Structure {
+        let mut structure = Structure::new(size, BlockType::Void(0));
+        for (i, pos) in structure.region_at(&Point3::new(0, 0, 0)).iter().enumerate() {
+            structure.set(&pos, BlockType::Solid(i));
+        }
+        structure
+    }
+
+    #[test]
+    fn rotating_swaps_x_and_z() {
+        let structure = numbered(Vector3::new(2, 1, 3));
+        let rotated = structure.rotated_y(1);
+        assert_eq!(rotated.size(), Vector3::new(3, 1, 2));
+        // Clockwise seen from above: the corner at min x and max z ends up
+        // at min x and min z.
+        assert_eq!(rotated.get(&Point3::new(0, 0, 0)), structure.get(&Point3::new(0, 0, 2)));
+        assert_eq!(rotated.get(&Point3::new(2, 0, 0)), structure.get(&Point3::new(0, 0, 0)));
+    }
+
+    #[test]
+    fn four_quarter_turns_are_the_identity() {
+        let structure = numbered(Vector3::new(2, 3, 4));
+        assert_eq!(structure.rotated_y(1).rotated_y(1).rotated_y(1).rotated_y(1), structure);
+        assert_eq!(structure.rotated_y(4), structure);
+        assert_eq!(structure.rotated_y(1).rotated_y(3), structure);
+        assert!(structure.rotated_y(2) != structure);
+    }
+
+    #[test]
+    fn mirroring_twice_is_the_identity() {
+        let structure = numbered(Vector3::new(2, 3, 4));
+        for axis in 0..3 {
+            let mirrored = structure.mirrored(axis);
+            assert!(mirrored != structure);
+            assert_eq!(mirrored.size(), structure.size());
+            assert_eq!(mirrored.mirrored(axis), structure);
+        }
+    }
+
+    #[test]
+    fn mirroring_flips_one_axis() {
+        let structure = numbered(Vector3::new(2, 3, 4));
+        let mirrored = structure.mirrored(2);
+        assert_eq!(mirrored.get(&Point3::new(1, 2, 0)), structure.get(&Point3::new(1, 2, 3)));
+    }
+
+    #[test]
+    fn copying_clips_the_region_to_the_world() {
+        let mut world = World::with_capacity(4);
+        world.add_block(BlockType::Solid(1), &Point3::new(3, 3, 3));
+
+        let structure = Structure::from_world(&world, &Region::new(&Point3::new(2, 2, 2), &Point3::new(usize::MAX, 9, 9))).unwrap();
+        assert_eq!(structure.size(), Vector3::new(2, 2, 2));
+        assert_eq!(structure.get(&Point3::new(1, 1, 1)), BlockType::Solid(1));
+        assert_eq!(Structure::from_world(&world, &Region::new(&Point3::new(4, 0, 0), &Point3::new(8, 3, 3))), None);
+    }
+}
@@ -1,9 +1,12 @@
 use std::cmp::min;
 
-use cgmath::Point3;
+use cgmath::{EuclideanSpace, Point3, Vector3};
 
 use util::types::Float;
 use model::block::{self, Block, BlockType};
+use world::region::Region;
+use world::structure::Structure;
+use world::history::ChangeSet;
 
 #[derive(Debug, Clone, Copy)]
 pub struct WorldBlock<'a> {
@@ -30,8 +33,17 @@ impl World {
         Self { blocks: vec![vec![vec![Block::default(); capacity]; capacity]; capacity] }
     }
 
+    /// Number of blocks along each axis.
+    pub fn size(&self) -> Vector3<usize> {
+        Vector3::new(self.blocks.len(), self.blocks[0].len(), self.blocks[0][0].len())
+    }
+
+    pub fn contains(&self, pos: &Point3<usize>) -> bool {
+        pos.x < self.blocks.len() && pos.y < self.blocks[0].len() && pos.z < self.blocks[0][0].len()
+    }
+
     pub fn add_block(&mut self, block_type: BlockType, pos: &Point3<usize>) {
-        if pos.x < self.blocks.len() && pos.y < self.blocks[0].len() && pos.z < self.blocks[0][0].len() {
+        if self.contains(pos) {
             self.blocks[pos.x][pos.y][pos.z] = Block::new(block_type);
         }
     }
@@ -40,7 +52,111 @@ impl World {
         self.blocks[pos.x][pos.y][pos.z] = Block::new(BlockType::Void(0));
     }
 
-    pub fn blocks<'a>(&self, center: &Point3<usize>, dim: usize) -> Vec<WorldBlock> {
+    pub fn block_type(&self, pos: &Point3<usize>) -> Option<BlockType> {
+        if self.contains(pos) {
+            Some(self.blocks[pos.x][pos.y][pos.z].get_type())
+        } else {
+            None
+        }
+    }
+
+    /// All solid blocks in the world, in x, y, z order.
+    pub fn solid_blocks(&self) -> Vec<(Point3<usize>, BlockType)> {
+        let mut blocks = Vec::new();
+        for (i, c) in self.blocks.iter().enumerate() {
+            for (j, c) in c.iter().enumerate() {
+                for (k, block) in c.iter().enumerate() {
+                    if let BlockType::Solid(_) = block.get_type() {
+                        blocks.push((Point3::new(i, j, k), block.get_type()));
+                    }
+                }
+            }
+        }
+        blocks
+    }
+
+    /// Calls ``f`` with every position in ``region`` that lies within the
+    /// world and its current block type, and replaces the block with the type
+    /// returned by ``f``, if any. Returns the blocks that changed.
+    fn edit_region<F>(&mut self, region: &Region, f: F) -> ChangeSet
+        where F: Fn(&Point3<usize>, BlockType) -> Option<BlockType>
+    {
+        let mut changes = ChangeSet::new();
+        let region = match region.clip(&self.size()) {
+            Some(region) => region,
+            None => return changes,
+        };
+
+        for pos in region.iter() {
+            let block = &mut self.blocks[pos.x][pos.y][pos.z];
+            if let Some(new_type) = f(&pos, block.get_type()) {
+                if new_type != block.get_type() {
+                    changes.push(pos, block.get_type(), new_type);
+                    *block = Block::new(new_type);
+                }
+            }
+        }
+        changes
+    }
+
+    /// Sets every block in ``changes`` to its new type.
+    pub fn apply(&mut self, changes: &ChangeSet) {
+        for change in changes.changes() {
+            if self.contains(&change.pos) {
+                self.blocks[change.pos.x][change.pos.y][change.pos.z] = Block::new(change.after);
+            }
+        }
+    }
+
+    /// Sets every block in ``region`` to ``block_type``.
+    pub fn fill(&mut self, region: &Region, block_type: BlockType) -> ChangeSet {
+        self.edit_region(region, |_, _| Some(block_type))
+    }
+
+    /// Changes all blocks of type ``from`` in ``region`` to ``to``.
+    pub fn replace(&mut self, region: &Region, from: BlockType, to: BlockType) -> ChangeSet {
+        self.edit_region(region, |_, current| {
+            if current.same_kind(&from) { Some(to) } else { None }
+        })
+    }
+
+    /// Turns ``region`` into a hollow box: the outer layer of blocks is set
+    /// to ``block_type`` and everything inside is cleared.
+    pub fn hollow(&mut self, region: &Region, block_type: BlockType) -> ChangeSet {
+        let bounds = *region;
+        self.edit_region(region, |pos, _| {
+            if bounds.is_on_boundary(pos) { Some(block_type) } else { Some(BlockType::Void(0)) }
+        })
+    }
+
+    /// Sets the blocks on the four vertical sides of ``region`` to
+    /// ``block_type``, leaving the rest untouched.
+    pub fn walls(&mut self, region: &Region, block_type: BlockType) -> ChangeSet {
+        let bounds = *region;
+        self.edit_region(region, |pos, _| {
+            if bounds.is_on_walls(pos) { Some(block_type) } else { None }
+        })
+    }
+
+    /// Copies the blocks in the part of ``region`` that lies within the world
+    /// into a structure, or returns ``None`` if no part of it does.
+    pub fn copy(&self, region: &Region) -> Option<Structure> {
+        Structure::from_world(self, region)
+    }
+
+    /// Pastes ``structure`` with its minimum corner at ``origin``. Parts that
+    /// fall outside the world are dropped. With ``skip_air`` the air blocks
+    /// of the structure leave the world untouched instead of clearing it.
+    pub fn paste(&mut self, structure: &Structure, origin: &Point3<usize>, skip_air: bool) -> ChangeSet {
+        self.edit_region(&structure.region_at(origin), |pos, _| {
+            match structure.get(&Point3::from_vec(*pos - *origin)) {
+                BlockType::Void(_) if skip_air => None,
+                block_type => Some(block_type),
+            }
+        })
+    }
+
+    pub fn blocks(&self, center: &Point3<usize>, dim: usize) -> Vec<WorldBlock<'_>> {
         let mut blocks = Vec::new();
         let (x, y, z) = (center.x, center.y, center.z);
         let range_x = x.saturating_sub(dim) .. min(x + dim, self.blocks.len());
@@ -50,7 +166,7 @@ impl World {
                 let range_z = z.saturating_sub(dim) .. min(z + dim, c.len());
                 for (k, block) in range_z.clone().zip(c[range_z].iter()) {
                     if let BlockType::Solid(_) = block.get_type() {
-                        blocks.push(WorldBlock { block: block, pos: Point3::new(i, j, k) });
+                        blocks.push(WorldBlock { block, pos: Point3::new(i, j, k) });
                     }
                 }
             }